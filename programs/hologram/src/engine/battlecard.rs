@@ -1,12 +1,24 @@
 use {
-    super::{ConcretePowerup, FightOutcome, PowerUp},
+    super::{
+        ConcretePowerup, Environment, FightOutcome, PowerUp, PowerupKind,
+        SpaceShipBattleCardSnapshot, TieBreaker,
+    },
     crate::{
-        state::{HitPoints, RepairTarget, Shots, SpaceShip, WeaponType},
-        utils::RandomNumberGenerator,
-        BASE_DODGE_CHANCE, BASE_HULL_HITPOINTS, BASE_JAMMING_NULLIFYING_CHANCE, BASE_JAM_CHANCE,
-        BASE_SHIELD_LAYERS, DODGE_CHANCE_CAP, JAMMING_NULLIFYING_CHANCE_CAP,
+        state::{
+            Drone, DroneSize, Gambit, HitPoints, Hull, Module, ModuleClass, Mutation, PowerUpType,
+            RepairTarget, ShipModifier, Shots, SpaceShip, StatusKind, Targeting, Trigger,
+            WeaponType,
+        },
+        utils::{LimitedString, RandomNumberGenerator},
+        ASTEROID_FIELD_MISSILE_DAMAGE_MALUS, BASE_ARMOR_HITPOINTS, BASE_CAPACITOR_CAPACITY,
+        BASE_CAPACITOR_RECHARGE_PER_TURN, BASE_CRITICAL_HIT_CHANCE, BASE_CRITICAL_HIT_MULTIPLIER,
+        BASE_JAM_CHANCE, CHARGE_PER_TURN, CRITICAL_HIT_CHANCE_CAP, CRITICAL_HIT_MULTIPLIER_CAP,
+        DODGE_CHANCE_CAP, INITIATIVE_CAP, JAMMING_NULLIFYING_CHANCE_CAP, NEBULA_DODGE_CHANCE_BONUS,
+        PROJECTILE_ARMOR_DAMAGE_MULTIPLIER, SOLAR_FLARE_LASER_DAMAGE_BONUS,
+        STATUS_EFFECT_MAX_STACKS,
     },
-    std::cmp::max,
+    anchor_lang::prelude::*,
+    std::cmp::{max, min},
 };
 
 // Note: Recently == 5 last turns
@@ -14,85 +26,325 @@ pub struct SpaceShipBattleCard {
     pub name: String,
     pub id: u64,
     // stats ----------------------------------------
-    // Hitpoints
+    // Hitpoints (damages go through shield, then armor, then hull)
     pub hull_hitpoints: HitPoints,
+    pub armor_hitpoints: HitPoints,
     pub shield_layers: HitPoints,
     // chance to avoid attacks/jams
     pub dodge_chance: u8,
     pub jamming_nullifying_chance: u8,
+    // chance for each shot to deal critical_hit_multiplier percent of its damage
+    pub critical_hit_chance: u8,
+    pub critical_hit_multiplier: u8,
+    // effects of the spaceship with the highest initiative resolve first each turn
+    pub initiative: u8,
+    // energy drained by the active powerups activations
+    pub capacitor: HitPoints,
+    pub capacitor_recharge_per_turn: u8,
     // powerups -------------------------------------
     pub concrete_powerups: Vec<ConcretePowerup>,
+    // drones flying around the spaceship, they can be shot down
+    pub launched_drones: Vec<LaunchedDrone>,
+    // timed buffs and debuffs currently affecting the spaceship
+    pub status_effects: Vec<StatusEffect>,
+    // what happened to the spaceship since the engine last dispatched triggers to the reactive powerups
+    pub pending_triggers: Vec<TriggerEvent>,
     // data -----------------------------------------
     // Note: data internal to the game engine that is updated along the match
     // for CapacitativeArmor
     //
     // Stores last 5 turns damages to the hull
     pub recent_hull_damage_per_turn: Vec<u8>,
+    // hitpoints lost to the opponent since the start of the fight, for tie-breaks
+    pub damage_taken: u16,
+}
+
+// What a battlecard is built from, taken from the spaceship before the fight
+// Note: emitted with the match results so that the fight can be replayed off-chain
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct FightLoadout {
+    pub name: LimitedString,
+    pub id: u64,
+    pub hull: Hull,
+    pub modules: Vec<Module>,
+    pub drones: Vec<Drone>,
+    pub mutations: Vec<Mutation>,
+    pub gambits: Vec<Gambit>,
+}
+
+impl FightLoadout {
+    pub fn new(spaceship: &SpaceShip) -> Self {
+        Self {
+            name: spaceship.name,
+            id: spaceship.id,
+            hull: spaceship.hull,
+            modules: spaceship.modules.clone(),
+            drones: spaceship.drones.clone(),
+            mutations: spaceship.mutations.clone(),
+            gambits: spaceship.gambits.clone(),
+        }
+    }
+
+    // position of a fitted power-up once modules, drones and mutations are put together (as in the battlecard)
+    pub fn powerup_position(&self, powerup_type: PowerUpType, index: u8) -> Option<usize> {
+        let index = index as usize;
+        match powerup_type {
+            PowerUpType::Module if index < self.modules.len() => Some(index),
+            PowerUpType::Drone if index < self.drones.len() => Some(self.modules.len() + index),
+            PowerUpType::Mutation if index < self.mutations.len() => {
+                Some(self.modules.len() + self.drones.len() + index)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl SpaceShipBattleCard {
     // Initialize a battlecard from a spaceship
     pub fn new(spaceship: &SpaceShip) -> Self {
+        Self::from_loadout(&FightLoadout::new(spaceship))
+    }
+
+    // Initialize a battlecard from a loadout
+    pub fn from_loadout(loadout: &FightLoadout) -> Self {
         // convert all modules, drones, mutations to PowerUp
-        let powerups: Vec<Box<dyn PowerUp>> = spaceship
+        let powerups: Vec<Box<dyn PowerUp>> = loadout
             .modules
             .iter()
             .map(|item| Box::new(item.clone()) as Box<dyn PowerUp>)
             .chain(
-                spaceship
+                loadout
                     .drones
                     .iter()
                     .map(|item| Box::new(item.clone()) as Box<dyn PowerUp>),
             )
             .chain(
-                spaceship
+                loadout
                     .mutations
                     .iter()
                     .map(|item| Box::new(item.clone()) as Box<dyn PowerUp>),
             )
             .collect();
 
-        // initialize stats
-        let mut hull_hitpoints = HitPoints::init(BASE_HULL_HITPOINTS);
-        let mut shield_layers = HitPoints::init(BASE_SHIELD_LAYERS);
-        let mut dodge_chance = BASE_DODGE_CHANCE;
-        let mut jamming_nullifying_chance = BASE_JAMMING_NULLIFYING_CHANCE;
+        // initialize stats from the hull
+        let hull_profile = loadout.hull.profile();
+        let mut hull_hitpoints = HitPoints::init(hull_profile.hull_hitpoints);
+        let mut armor_hitpoints = HitPoints::init(BASE_ARMOR_HITPOINTS);
+        let mut shield_layers = HitPoints::init(hull_profile.shield_layers);
+        let mut dodge_chance = hull_profile.dodge_chance;
+        let mut jamming_nullifying_chance = hull_profile.jamming_nullifying_chance;
+        let mut critical_hit_chance = BASE_CRITICAL_HIT_CHANCE;
+        let mut critical_hit_multiplier = BASE_CRITICAL_HIT_MULTIPLIER;
+        let mut initiative = hull_profile.initiative;
         // apply all bonuses from powerups
         powerups
             .iter()
             .filter_map(|p| p.get_bonuses())
             .for_each(|bonuses| {
                 hull_hitpoints.increase_max(bonuses.hull_hitpoints);
+                armor_hitpoints.increase_max(bonuses.armor_hitpoints);
                 shield_layers.increase_max(bonuses.shield_layers);
                 dodge_chance += bonuses.dodge_chance;
                 jamming_nullifying_chance += bonuses.jamming_nullifying_chance;
+                critical_hit_chance =
+                    critical_hit_chance.saturating_add(bonuses.critical_hit_chance);
+                critical_hit_multiplier =
+                    critical_hit_multiplier.saturating_add(bonuses.critical_hit_damage);
+                initiative = initiative.saturating_add(bonuses.initiative);
             });
         // Cap dodge chances and Jammin nullyfing resistance chances
-        dodge_chance = max(dodge_chance, DODGE_CHANCE_CAP);
-        jamming_nullifying_chance = max(jamming_nullifying_chance, JAMMING_NULLIFYING_CHANCE_CAP);
+        dodge_chance = min(dodge_chance, DODGE_CHANCE_CAP);
+        jamming_nullifying_chance = min(jamming_nullifying_chance, JAMMING_NULLIFYING_CHANCE_CAP);
+        critical_hit_chance = min(critical_hit_chance, CRITICAL_HIT_CHANCE_CAP);
+        critical_hit_multiplier = min(critical_hit_multiplier, CRITICAL_HIT_MULTIPLIER_CAP);
+        initiative = min(initiative, INITIATIVE_CAP);
 
-        let concrete_powerups: Vec<ConcretePowerup> = powerups
+        // afterburners strip hull plating for speed
+        for module in loadout.modules.iter() {
+            if let ModuleClass::Afterburner(_, stats) = &module.class {
+                hull_hitpoints.decrease_max(stats.hull_penalty);
+            }
+        }
+
+        // capacitor, grown by the capacitor modules
+        let mut capacitor = HitPoints::init(BASE_CAPACITOR_CAPACITY);
+        let mut capacitor_recharge_per_turn = BASE_CAPACITOR_RECHARGE_PER_TURN;
+        for module in loadout.modules.iter() {
+            if let ModuleClass::Capacitor(_, cms) = &module.class {
+                capacitor.increase_max(cms.capacity);
+                capacitor_recharge_per_turn =
+                    capacitor_recharge_per_turn.saturating_add(cms.recharge_per_turn);
+            }
+        }
+
+        // spaceship wide modifiers
+        let modifiers: Vec<ShipModifier> =
+            powerups.iter().filter_map(|p| p.get_modifier()).collect();
+
+        let mut concrete_powerups: Vec<ConcretePowerup> = powerups
             .into_iter()
             .map(|powerup| ConcretePowerup::new(powerup))
             .collect();
 
+        // apply all modifiers to the powerups
+        for modifier in modifiers {
+            for p in concrete_powerups.iter_mut() {
+                match modifier {
+                    ShipModifier::ChargeTimeReduction(amount) => {
+                        if p.is_active() {
+                            p.charge_time = max(p.charge_time.saturating_sub(amount), 1);
+                        }
+                    }
+                    ShipModifier::WeaponDamage(weapon_type, bonus) => {
+                        if let Some(effect) = p.effect.as_mut() {
+                            effect.increase_weapon_damage(weapon_type, bonus);
+                        }
+                    }
+                }
+            }
+        }
+
+        // player gambits hold their powerup until their condition is met
+        for gambit in loadout.gambits.iter() {
+            if let Some(index) = loadout.powerup_position(gambit.powerup_type, gambit.index) {
+                concrete_powerups[index].gambit = Some(gambit.condition.clone());
+            }
+        }
+
+        // all drones are launched when the fight starts
+        let launched_drones = concrete_powerups
+            .iter()
+            .enumerate()
+            .filter_map(|(powerup_index, p)| match p.og_kind {
+                PowerupKind::Drone { size, .. } => Some(LaunchedDrone {
+                    powerup_index,
+                    size,
+                    hitpoints: HitPoints::init(size.hitpoints()),
+                }),
+                _ => None,
+            })
+            .collect();
+
         Self {
-            name: spaceship.name.to_string(),
-            id: spaceship.id,
+            name: loadout.name.to_string(),
+            id: loadout.id,
             hull_hitpoints,
+            armor_hitpoints,
             shield_layers,
             dodge_chance,
             jamming_nullifying_chance,
+            critical_hit_chance,
+            critical_hit_multiplier,
+            initiative,
+            capacitor,
+            capacitor_recharge_per_turn,
             concrete_powerups,
+            launched_drones,
+            status_effects: vec![],
+            pending_triggers: vec![],
             recent_hull_damage_per_turn: vec![0, 0, 0, 0, 0],
+            damage_taken: 0,
+        }
+    }
+
+    // Alter the spaceship for the environment of the match, before it starts
+    pub fn apply_environment(&mut self, environment: Environment) {
+        match environment {
+            Environment::DeepSpace => {}
+            Environment::IonStorm => {
+                self.shield_layers = HitPoints::init(self.shield_layers.max / 2);
+            }
+            Environment::Nebula => {
+                self.dodge_chance = min(
+                    self.dodge_chance.saturating_add(NEBULA_DODGE_CHANCE_BONUS),
+                    DODGE_CHANCE_CAP,
+                );
+            }
+            Environment::AsteroidField => {
+                for effect in self
+                    .concrete_powerups
+                    .iter_mut()
+                    .filter_map(|p| p.effect.as_mut())
+                {
+                    effect.decrease_weapon_damage(
+                        WeaponType::Missile,
+                        ASTEROID_FIELD_MISSILE_DAMAGE_MALUS,
+                    );
+                }
+            }
+            Environment::SolarFlare => {
+                for effect in self
+                    .concrete_powerups
+                    .iter_mut()
+                    .filter_map(|p| p.effect.as_mut())
+                {
+                    effect
+                        .increase_weapon_damage(WeaponType::Laser, SOLAR_FLARE_LASER_DAMAGE_BONUS);
+                }
+            }
+        }
+    }
+
+    // Serializable copy of the battlecard current state, used for the fight log
+    pub fn snapshot(&self) -> SpaceShipBattleCardSnapshot {
+        SpaceShipBattleCardSnapshot {
+            name: self.name.clone(),
+            id: self.id,
+            hull_hitpoints: self.hull_hitpoints.clone(),
+            armor_hitpoints: self.armor_hitpoints.clone(),
+            shield_layers: self.shield_layers.clone(),
+            dodge_chance: self.dodge_chance,
+            jamming_nullifying_chance: self.jamming_nullifying_chance,
+            critical_hit_chance: self.critical_hit_chance,
+            critical_hit_multiplier: self.critical_hit_multiplier,
+            initiative: self.initiative,
+            capacitor: self.capacitor.clone(),
+            powerups: self
+                .concrete_powerups
+                .iter()
+                .map(|p| p.name.to_string())
+                .collect(),
         }
     }
 
     // Maintenance operation to be carried each turn for the game engine
-    pub fn end_of_turn_internals(&mut self) {
+    pub fn end_of_turn_internals(&mut self, event_callback: &mut dyn FnMut(BattleEvent)) {
+        // damage over time
+        let burn_damage = self
+            .status_effects
+            .iter()
+            .map(|s| match s.kind {
+                StatusKind::Burn { damage } => damage.saturating_mul(s.stacks),
+                _ => 0,
+            })
+            .fold(0u8, |acc, damage| acc.saturating_add(damage));
+        if burn_damage > 0 {
+            let hitpoints = self.total_hitpoints();
+            self.apply_hull_damage(burn_damage, event_callback);
+            self.record_damage_taken(hitpoints);
+        }
+
+        // tick status effects and remove the expired ones
+        let origin_id = self.id;
+        self.status_effects.retain_mut(|s| {
+            s.remaining_turns = s.remaining_turns.saturating_sub(1);
+            if s.remaining_turns == 0 {
+                event_callback(BattleEvent::StatusExpired {
+                    origin_id,
+                    status: s.kind,
+                });
+                return false;
+            }
+            true
+        });
+
         // advance recent hull damage
         self.recent_hull_damage_per_turn.pop();
         self.recent_hull_damage_per_turn.insert(0, 0);
+
+        // recharge the capacitor
+        self.capacitor
+            .resplenish_up_to_max(self.capacitor_recharge_per_turn);
     }
 
     // A spaceship is defeated when his Hull HP reaches 0
@@ -100,10 +352,175 @@ impl SpaceShipBattleCard {
         self.hull_hitpoints.depleted()
     }
 
+    // hull, armor and shield layers left
+    pub fn total_hitpoints(&self) -> u16 {
+        self.hull_hitpoints.current as u16
+            + self.armor_hitpoints.current as u16
+            + self.shield_layers.current as u16
+    }
+
+    fn record_damage_taken(&mut self, hitpoints_before: u16) {
+        let damage = hitpoints_before.saturating_sub(self.total_hitpoints());
+        self.damage_taken = self.damage_taken.saturating_add(damage);
+    }
+
+    // hull and armor hitpoints left (current, max), shield layers don't count
+    pub fn remaining_hitpoints(&self) -> (u16, u16) {
+        (
+            self.hull_hitpoints.current as u16 + self.armor_hitpoints.current as u16,
+            self.hull_hitpoints.max as u16 + self.armor_hitpoints.max as u16,
+        )
+    }
+
+    // damage of the escalation phase, not dealt by the opponent
+    pub fn apply_attrition_damage(
+        &mut self,
+        damage: u8,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) {
+        event_callback(BattleEvent::Attrition {
+            origin_id: self.id,
+            damage,
+        });
+        self.apply_hull_damage(damage, event_callback);
+    }
+
     pub fn recent_hull_damage(&self) -> u8 {
         self.recent_hull_damage_per_turn.iter().sum()
     }
 
+    // Apply a status for `duration` turns
+    // An identical status already in place gets its duration refreshed, and gains a stack if stackable
+    pub fn apply_status(
+        &mut self,
+        status: StatusKind,
+        duration: u8,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) {
+        if duration == 0 {
+            return;
+        }
+        let status_effect = match self.status_effects.iter_mut().find(|s| s.kind == status) {
+            Some(existing) => {
+                existing.remaining_turns = max(existing.remaining_turns, duration);
+                if status.is_stackable() {
+                    existing.stacks = min(existing.stacks + 1, STATUS_EFFECT_MAX_STACKS);
+                }
+                existing.clone()
+            }
+            None => {
+                let status_effect = StatusEffect {
+                    kind: status,
+                    remaining_turns: duration,
+                    stacks: 1,
+                };
+                self.status_effects.push(status_effect.clone());
+                status_effect
+            }
+        };
+        event_callback(BattleEvent::StatusApplied {
+            origin_id: self.id,
+            status: status_effect.kind,
+            remaining_turns: status_effect.remaining_turns,
+            stacks: status_effect.stacks,
+        });
+    }
+
+    pub fn drones_disabled(&self) -> bool {
+        self.status_effects
+            .iter()
+            .any(|s| s.kind == StatusKind::DronesDisabled)
+    }
+
+    // dodge chance once status penalties are accounted for
+    pub fn effective_dodge_chance(&self) -> u8 {
+        let penalty = self
+            .status_effects
+            .iter()
+            .map(|s| match s.kind {
+                StatusKind::DodgePenalty { amount } => amount.saturating_mul(s.stacks),
+                _ => 0,
+            })
+            .fold(0u8, |acc, penalty| acc.saturating_add(penalty));
+        self.dodge_chance.saturating_sub(penalty)
+    }
+
+    // charge gained each turn by active powerups once status modifiers are accounted for
+    pub fn charge_per_turn(&self) -> u8 {
+        let modifier: i16 = self
+            .status_effects
+            .iter()
+            .map(|s| match s.kind {
+                StatusKind::ChargeRate { modifier } => modifier as i16 * s.stacks as i16,
+                _ => 0,
+            })
+            .sum();
+        (CHARGE_PER_TURN as i16 + modifier).clamp(0, u8::MAX as i16) as u8
+    }
+
+    // damage the active powerups can deal over 100 turns, used to pick targets in fleet fights
+    pub fn threat(&self) -> u16 {
+        let threat: u32 = self
+            .concrete_powerups
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| p.is_active() && p.charge_time > 0 && self.can_charge(*i))
+            .filter_map(|(_, p)| {
+                p.effect
+                    .as_ref()
+                    .map(|e| e.potential_damage() as u32 * 100 / p.charge_time as u32)
+            })
+            .sum();
+        min(threat, u16::MAX as u32) as u16
+    }
+
+    // whether a reactive powerup subscribed to `trigger` reacts to what happened
+    pub fn reacts_to(&self, trigger: Trigger, event: TriggerEvent) -> bool {
+        match (trigger, event) {
+            (Trigger::OnHit, TriggerEvent::Hit)
+            | (Trigger::OnDodge, TriggerEvent::Dodge)
+            | (Trigger::OnShieldLayerDown, TriggerEvent::ShieldLayerDown)
+            | (Trigger::OnJammed, TriggerEvent::Jammed)
+            | (Trigger::OnOpponentRepair, TriggerEvent::OpponentRepair) => true,
+            (Trigger::OnHullBelowPercent(percent), TriggerEvent::HullDamaged) => {
+                (self.hull_hitpoints.current as u16) * 100
+                    < (self.hull_hitpoints.max as u16) * (percent as u16)
+            }
+            _ => false,
+        }
+    }
+
+    // whether the powerup gains charge this turn
+    pub fn can_charge(&self, powerup_index: usize) -> bool {
+        match self.concrete_powerups[powerup_index].og_kind {
+            PowerupKind::Drone { .. } => {
+                !self.drones_disabled() && !self.is_drone_destroyed(powerup_index)
+            }
+            _ => true,
+        }
+    }
+
+    // whether the powerup is a drone that has been shot down
+    pub fn is_drone_destroyed(&self, powerup_index: usize) -> bool {
+        self.launched_drones
+            .iter()
+            .any(|d| d.powerup_index == powerup_index && d.is_destroyed())
+    }
+
+    pub fn has_launched_drones(&self) -> bool {
+        self.launched_drones.iter().any(|d| !d.is_destroyed())
+    }
+
+    // the most charge accumulated by one of the active powerups
+    pub fn highest_charge(&self) -> u8 {
+        self.concrete_powerups
+            .iter()
+            .filter(|p| p.is_active())
+            .map(|p| p.accumulated_charge)
+            .max()
+            .unwrap_or(0)
+    }
+
     // return a MUTABLE iterator over the active powerups. You can then use this iterator to modify the active powerups
     fn get_active_powerups_mutable_iterator(
         &mut self,
@@ -113,16 +530,18 @@ impl SpaceShipBattleCard {
             .filter(|p| p.og_powerup.is_active())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn fire_at(
         &mut self,
         target: &mut SpaceShipBattleCard,
         rng: &mut RandomNumberGenerator,
         damage: u8,
+        max_damage: Option<u8>,
         shots: Shots,
         weapon_type: WeaponType,
+        targeting: Targeting,
         event_callback: &mut dyn FnMut(BattleEvent),
     ) {
-        #[cfg(any(test, feature = "render-hooks"))]
         event_callback(BattleEvent::Fire {
             origin_id: self.id,
             target_id: target.id,
@@ -131,30 +550,156 @@ impl SpaceShipBattleCard {
             shots,
         });
 
+        // Note: drones are hit for the base damage, variance and critical hits only apply to the spaceship
+        if targeting == Targeting::DronesFirst && target.has_launched_drones() {
+            target.take_fire_on_drones(rng, damage, shots, event_callback);
+            return;
+        }
+
         // Dodge roll
         match weapon_type {
             WeaponType::Plasma | WeaponType::Missile => { /* attacks cannot be dodged */ }
             _ => {
                 let hit_roll = rng.roll_dice(100);
-                let did_hit = hit_roll >= target.dodge_chance as u64;
+                let did_hit = hit_roll >= target.effective_dodge_chance() as u64;
                 if !did_hit {
-                    #[cfg(any(test, feature = "render-hooks"))]
                     event_callback(BattleEvent::Dodge { origin_id: self.id });
+                    target.pending_triggers.push(TriggerEvent::Dodge);
                     return;
                 }
             }
         }
 
-        match shots {
-            Shots::Single | Shots::Salvo(1) => {
-                target.apply_damage(damage, weapon_type, event_callback)
-            }
-            Shots::Salvo(shots) => {
-                for _ in 0..shots {
-                    target.apply_damage(damage, weapon_type, event_callback);
-                }
+        for _ in 0..shots.count() {
+            let damage = self.roll_shot_damage(target.id, rng, damage, max_damage, event_callback);
+            target.take_shot(rng, damage, weapon_type, event_callback);
+        }
+    }
+
+    // Damage of a single shot, rolled within the weapon damage range then for a critical hit
+    // Note: nothing is rolled for fixed damage weapons and spaceships without critical hit chance
+    fn roll_shot_damage(
+        &self,
+        target_id: u64,
+        rng: &mut RandomNumberGenerator,
+        damage: u8,
+        max_damage: Option<u8>,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) -> u8 {
+        let mut damage = match max_damage {
+            Some(max_damage) if max_damage > damage => {
+                rng.range(damage as u64, max_damage as u64 + 1) as u8
             }
+            _ => damage,
         };
+        if self.critical_hit_chance > 0 && rng.roll_dice(100) <= self.critical_hit_chance as u64 {
+            damage = min(
+                damage as u16 * self.critical_hit_multiplier as u16 / 100,
+                u8::MAX as u16,
+            ) as u8;
+            event_callback(BattleEvent::CriticalHit {
+                origin_id: self.id,
+                target_id,
+                damage,
+            });
+        }
+        damage
+    }
+
+    fn take_shot(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        damage: u8,
+        weapon_type: WeaponType,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) {
+        if weapon_type == WeaponType::Missile && self.intercept_missile(rng, event_callback) {
+            return;
+        }
+        self.apply_damage(damage, weapon_type, event_callback);
+    }
+
+    // Point defense powerups ready to fire try to shoot the missile down, one after the other
+    // Each attempt heats the powerup, so large salvos get through
+    fn intercept_missile(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) -> bool {
+        for powerup_index in 0..self.concrete_powerups.len() {
+            if !self.can_intercept(powerup_index) {
+                continue;
+            }
+            let powerup = &mut self.concrete_powerups[powerup_index];
+            let intercept_chance = powerup.intercept_chance.unwrap_or(0);
+            powerup.heat();
+            if rng.roll_dice(100) <= intercept_chance as u64 {
+                event_callback(BattleEvent::MissileIntercepted {
+                    origin_id: self.id,
+                    powerup_index: powerup_index as u8,
+                });
+                return true;
+            }
+        }
+        false
+    }
+
+    // whether the powerup is a point defense ready to fire (disabled or shot down drones can't)
+    pub fn can_intercept(&self, powerup_index: usize) -> bool {
+        let powerup = &self.concrete_powerups[powerup_index];
+        powerup.intercept_chance.is_some()
+            && powerup.is_off_cooldown()
+            && self.can_charge(powerup_index)
+    }
+
+    // each shot hits one of the drones still flying, at random (drones are too small to dodge)
+    // shots fired once all drones are down are lost
+    fn take_fire_on_drones(
+        &mut self,
+        rng: &mut RandomNumberGenerator,
+        damage: u8,
+        shots: Shots,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) {
+        for _ in 0..shots.count() {
+            let flying_drones: Vec<usize> = self
+                .launched_drones
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| !d.is_destroyed())
+                .map(|(i, _)| i)
+                .collect();
+            if flying_drones.is_empty() {
+                return;
+            }
+            let drone_index = flying_drones[rng.roll_dice(flying_drones.len()) as usize - 1];
+            self.apply_drone_damage(drone_index, damage, event_callback);
+        }
+    }
+
+    fn apply_drone_damage(
+        &mut self,
+        drone_index: usize,
+        damage: u8,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) {
+        let drone = &mut self.launched_drones[drone_index];
+        let damage = min(damage, drone.hitpoints.current);
+        drone.hitpoints.deplete(damage);
+        event_callback(BattleEvent::DroneDamaged {
+            origin_id: self.id,
+            powerup_index: drone.powerup_index as u8,
+            damage,
+        });
+        if drone.is_destroyed() {
+            let powerup_index = drone.powerup_index;
+            // whatever the drone was charging is lost with it
+            self.concrete_powerups[powerup_index].accumulated_charge = 0;
+            event_callback(BattleEvent::DroneDestroyed {
+                origin_id: self.id,
+                powerup_index: powerup_index as u8,
+            });
+        }
     }
 
     pub fn jam(
@@ -162,10 +707,10 @@ impl SpaceShipBattleCard {
         target: &mut SpaceShipBattleCard,
         rng: &mut RandomNumberGenerator,
         charge_burn: u8,
+        capacitor_drain: u8,
         event_callback: &mut dyn FnMut(BattleEvent),
     ) {
         let jam_chance = BASE_JAM_CHANCE.saturating_sub(target.jamming_nullifying_chance);
-        #[cfg(any(test, feature = "render-hooks"))]
         event_callback(BattleEvent::Jam {
             origin_id: self.id,
             target_id: target.id,
@@ -174,6 +719,20 @@ impl SpaceShipBattleCard {
         });
         if rng.roll_dice(BASE_JAM_CHANCE as usize) <= jam_chance as u64 {
             let target_id = target.id;
+            if capacitor_drain > 0 {
+                let drained = min(capacitor_drain, target.capacitor.current);
+                target.capacitor.deplete(drained);
+                event_callback(BattleEvent::CapacitorDrained {
+                    origin_id: self.id,
+                    target_id,
+                    amount: drained,
+                });
+            }
+            // pure energy neutralizers don't burn charges
+            if charge_burn == 0 {
+                target.pending_triggers.push(TriggerEvent::Jammed);
+                return;
+            }
             let active_powerups_iter_mut = target.get_active_powerups_mutable_iterator();
             // filter powerups to get the active one, with some accumulated_charge
             let mut active_powerups_iter_mut_with_charge = active_powerups_iter_mut
@@ -182,7 +741,6 @@ impl SpaceShipBattleCard {
 
             // if there is no active powerup with charge, we can't jam anything and abort
             if active_powerups_iter_mut_with_charge.peek().is_none() {
-                #[cfg(any(test, feature = "render-hooks"))]
                 event_callback(BattleEvent::NothingToJam {
                     origin_id: self.id,
                     target_id,
@@ -195,14 +753,13 @@ impl SpaceShipBattleCard {
             let mut active_powerups_with_charge: Vec<&mut ConcretePowerup> =
                 active_powerups_iter_mut_with_charge.collect();
             let random_index = rng.roll_dice(active_powerups_with_charge.len()) as usize - 1;
-            #[cfg(any(test, feature = "render-hooks"))]
             {
                 let target_powerup_name = active_powerups_with_charge[random_index].name.clone();
                 event_callback(BattleEvent::ActivePowerUpJammed {
                     origin_id: self.id,
                     target_id,
                     active_power_up_name: target_powerup_name.to_string(),
-                    active_power_up_index: random_index,
+                    active_power_up_index: random_index as u8,
                     charge_burn,
                 });
             }
@@ -210,8 +767,8 @@ impl SpaceShipBattleCard {
                 active_powerups_with_charge[random_index]
                     .accumulated_charge
                     .saturating_sub(charge_burn);
+            target.pending_triggers.push(TriggerEvent::Jammed);
         } else {
-            #[cfg(any(test, feature = "render-hooks"))]
             event_callback(BattleEvent::JamResisted { origin_id: self.id });
         }
     }
@@ -222,30 +779,65 @@ impl SpaceShipBattleCard {
         weapon_type: WeaponType,
         event_callback: &mut dyn FnMut(BattleEvent),
     ) {
+        self.pending_triggers.push(TriggerEvent::Hit);
+        let hitpoints = self.total_hitpoints();
         match weapon_type {
-            WeaponType::Projectile => self.apply_hull_damage(damage, event_callback),
-            WeaponType::Missile => self.apply_hull_damage(damage, event_callback),
+            WeaponType::Projectile => self.apply_armor_then_hull_damage(
+                damage,
+                PROJECTILE_ARMOR_DAMAGE_MULTIPLIER,
+                event_callback,
+            ),
+            WeaponType::Missile => self.apply_armor_then_hull_damage(damage, 1, event_callback),
             WeaponType::Laser => {
                 if self.shield_layers.depleted() {
-                    self.apply_hull_damage(damage, event_callback)
+                    self.apply_armor_then_hull_damage(damage, 1, event_callback)
                 } else {
                     self.deplete_shield_layer(event_callback);
                 }
             }
             WeaponType::Plasma => {
-                // only inflicts damage if shields are down
+                // only inflicts damage if shields are down, but melts through armor
                 if self.shield_layers.depleted() {
                     self.apply_hull_damage(damage, event_callback)
                 } else {
-                    #[cfg(any(test, feature = "render-hooks"))]
                     event_callback(BattleEvent::ShieldCounterPlasmaAttack { origin_id: self.id });
                 }
             }
         }
+        self.record_damage_taken(hitpoints);
+    }
+
+    // armor soaks the damage first (multiplied by armor_damage_multiplier), what goes through reaches the hull at its base value
+    fn apply_armor_then_hull_damage(
+        &mut self,
+        damage: u8,
+        armor_damage_multiplier: u8,
+        event_callback: &mut dyn FnMut(BattleEvent),
+    ) {
+        if self.armor_hitpoints.depleted() {
+            self.apply_hull_damage(damage, event_callback);
+            return;
+        }
+        let armor_damage = min(
+            damage.saturating_mul(armor_damage_multiplier),
+            self.armor_hitpoints.current,
+        );
+        event_callback(BattleEvent::ArmorDamaged {
+            origin_id: self.id,
+            damage: armor_damage,
+        });
+        self.armor_hitpoints.deplete(armor_damage);
+
+        // rounded up, widened as the armor can be close to u8::MAX
+        let absorbed_damage = ((armor_damage as u16 + armor_damage_multiplier as u16 - 1)
+            / armor_damage_multiplier as u16) as u8;
+        let hull_damage = damage.saturating_sub(absorbed_damage);
+        if hull_damage > 0 {
+            self.apply_hull_damage(hull_damage, event_callback);
+        }
     }
 
     fn apply_hull_damage(&mut self, damage: u8, event_callback: &mut dyn FnMut(BattleEvent)) {
-        #[cfg(any(test, feature = "render-hooks"))]
         event_callback(BattleEvent::HullDamaged {
             origin_id: self.id,
             damage,
@@ -254,18 +846,58 @@ impl SpaceShipBattleCard {
         if let Some(last) = self.recent_hull_damage_per_turn.first_mut() {
             *last += damage;
         }
+        self.pending_triggers.push(TriggerEvent::HullDamaged);
     }
 
     fn deplete_shield_layer(&mut self, event_callback: &mut dyn FnMut(BattleEvent)) {
-        #[cfg(any(test, feature = "render-hooks"))]
         event_callback(BattleEvent::ShieldLayerDown { origin_id: self.id });
         self.shield_layers.deplete(1);
+        self.pending_triggers.push(TriggerEvent::ShieldLayerDown);
     }
 }
 
-#[cfg(any(test, feature = "render-hooks"))]
+// What happened to a spaceship, matched against the Trigger of its reactive powerups
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerEvent {
+    Hit,
+    Dodge,
+    ShieldLayerDown,
+    Jammed,
+    HullDamaged,
+    OpponentRepair,
+}
+
+// A drone of the spaceship, flying during the fight
+#[derive(Debug, Clone)]
+pub struct LaunchedDrone {
+    // the drone in the spaceship concrete_powerups
+    pub powerup_index: usize,
+    pub size: DroneSize,
+    pub hitpoints: HitPoints,
+}
+
+impl LaunchedDrone {
+    pub fn is_destroyed(&self) -> bool {
+        self.hitpoints.depleted()
+    }
+}
+
+// A status applied to a spaceship, see StatusKind
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    // the status is removed when it reaches 0
+    pub remaining_turns: u8,
+    pub stacks: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub enum BattleEvent {
     MatchStarted {},
+    // where the match takes place, see SpaceShipBattleCard::apply_environment
+    EnvironmentRevealed {
+        environment: Environment,
+    },
     TurnStart {
         turn: u16,
     },
@@ -291,6 +923,10 @@ pub enum BattleEvent {
         origin_id: u64,
         damage: u8,
     },
+    ArmorDamaged {
+        origin_id: u64,
+        damage: u8,
+    },
     ShieldLayerDown {
         origin_id: u64,
     },
@@ -311,7 +947,7 @@ pub enum BattleEvent {
         origin_id: u64,
         target_id: u64,
         active_power_up_name: String,
-        active_power_up_index: usize,
+        active_power_up_index: u8,
         charge_burn: u8,
     },
     Repair {
@@ -319,6 +955,211 @@ pub enum BattleEvent {
         repair_target: RepairTarget,
         amount: u8,
     },
+    // an active powerup is charged and releases its effect
+    PowerUpActivated {
+        origin_id: u64,
+        powerup_index: u8,
+    },
+    // a passive powerup effect went through and the powerup is now heating up
+    PassiveTriggered {
+        origin_id: u64,
+        powerup_index: u8,
+    },
+    // a status was applied or refreshed on the sbc
+    StatusApplied {
+        origin_id: u64,
+        status: StatusKind,
+        remaining_turns: u8,
+        stacks: u8,
+    },
+    StatusExpired {
+        origin_id: u64,
+        status: StatusKind,
+    },
+    // a reactive powerup is released by its trigger
+    PowerUpReacted {
+        origin_id: u64,
+        powerup_index: u8,
+        trigger: Trigger,
+    },
+    // one of the sbc drones was hit
+    DroneDamaged {
+        origin_id: u64,
+        powerup_index: u8,
+        damage: u8,
+    },
+    // one of the sbc drones was shot down, it won't charge anymore
+    DroneDestroyed {
+        origin_id: u64,
+        powerup_index: u8,
+    },
+    // the fight drags on, shields stop regenerating and attrition starts
+    EscalationStarted {},
+    // hull damage dealt by the escalation phase
+    Attrition {
+        origin_id: u64,
+        damage: u8,
+    },
+    // the fight didn't produce a winner, it was decided on points
+    TieBroken {
+        tie_breaker: TieBreaker,
+        outcome: FightOutcome,
+    },
+    // the sbc hull is depleted, it's out of the fight
+    SpaceShipDefeated {
+        origin_id: u64,
+    },
+    // a point defense powerup of the sbc shot an incoming missile down
+    MissileIntercepted {
+        origin_id: u64,
+        powerup_index: u8,
+    },
+    CapacitorDrained {
+        origin_id: u64,
+        target_id: u64,
+        amount: u8,
+    },
+    // a shot of the sbc landed a critical hit, dealing `damage`
+    CriticalHit {
+        origin_id: u64,
+        target_id: u64,
+        damage: u8,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            engine::LT_MUTATIONS_RARE,
+            state::{mock_spaceship, HitPoints, Hull},
+            utils::LimitedString,
+        },
+    };
+
+    #[test]
+    fn test_dodge_chance_cap() {
+        let kinetic_tendons = LT_MUTATIONS_RARE
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Kinetic Tendons"))
+            .unwrap();
+        for hull in [
+            Hull::CommonOne,
+            Hull::CommonTwo,
+            Hull::CommonThree,
+            Hull::UncommonOne,
+            Hull::UncommonTwo,
+            Hull::UncommonThree,
+            Hull::UncommonFour,
+            Hull::RareOne,
+            Hull::RareTwo,
+            Hull::FactionOne,
+        ] {
+            // the hull baseline is kept as is when below the cap, not raised to it
+            let mut spaceship = mock_spaceship(vec![], vec![], vec![]);
+            spaceship.hull = hull;
+            let baseline = hull.profile().dodge_chance;
+            assert_eq!(
+                SpaceShipBattleCard::new(&spaceship).dodge_chance,
+                min(baseline, DODGE_CHANCE_CAP)
+            );
+
+            // bonuses stack up to the cap
+            spaceship.mutations = vec![kinetic_tendons.clone(); 8];
+            assert_eq!(
+                SpaceShipBattleCard::new(&spaceship).dodge_chance,
+                DODGE_CHANCE_CAP
+            );
+        }
+    }
+
+    #[test]
+    fn test_armor_damage_near_max_armor() {
+        let spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut battlecard = SpaceShipBattleCard::new(&spaceship);
+        battlecard.armor_hitpoints = HitPoints::init(u8::MAX);
+        battlecard.hull_hitpoints = HitPoints::init(u8::MAX);
+
+        // the armor soaks 255 of the 400 multiplied damage, absorbing 128 of the 200 base damage
+        battlecard.apply_armor_then_hull_damage(200, 2, &mut |_| {});
+        assert!(battlecard.armor_hitpoints.depleted());
+        assert_eq!(battlecard.hull_hitpoints.current, u8::MAX - 72);
+    }
+
+    #[test]
+    fn test_armor_damage_interactions() {
+        let spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        s.armor_hitpoints = HitPoints::init(6);
+        let hull_max = s.hull_hitpoints.max;
+
+        // missiles are soaked by the armor
+        s.apply_damage(2, WeaponType::Missile, &mut |_| {});
+        assert_eq!(s.armor_hitpoints.current, 4);
+        assert_eq!(s.hull_hitpoints.current, hull_max);
+
+        // plasma melts through the armor
+        s.apply_damage(2, WeaponType::Plasma, &mut |_| {});
+        assert_eq!(s.armor_hitpoints.current, 4);
+        assert_eq!(s.hull_hitpoints.current, hull_max - 2);
+
+        // projectiles shred the armor, what's left goes through to the hull
+        s.apply_damage(3, WeaponType::Projectile, &mut |_| {});
+        assert_eq!(s.armor_hitpoints.current, 0);
+        assert_eq!(s.hull_hitpoints.current, hull_max - 2 - 1);
+    }
+
+    #[test]
+    fn test_status_effects_stack_and_expire() {
+        let spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let hull_max = s.hull_hitpoints.max;
+        let burn = StatusKind::Burn { damage: 1 };
+
+        // applying the same status again adds a stack and refreshes the duration
+        s.apply_status(burn, 2, &mut |_| {});
+        s.apply_status(burn, 3, &mut |_| {});
+        assert_eq!(s.status_effects.len(), 1);
+        assert_eq!(s.status_effects[0].stacks, 2);
+        assert_eq!(s.status_effects[0].remaining_turns, 3);
+
+        // non stackable statuses are only refreshed
+        s.apply_status(StatusKind::DronesDisabled, 1, &mut |_| {});
+        s.apply_status(StatusKind::DronesDisabled, 1, &mut |_| {});
+        assert!(s.drones_disabled());
+        assert_eq!(s.status_effects[1].stacks, 1);
+
+        let mut expired = vec![];
+        for _ in 0..3 {
+            s.end_of_turn_internals(&mut |event| {
+                if let BattleEvent::StatusExpired { status, .. } = event {
+                    expired.push(status);
+                }
+            });
+        }
+        // burned for 2 damage per turn during 3 turns
+        assert_eq!(s.hull_hitpoints.current, hull_max - 6);
+        assert_eq!(expired, vec![StatusKind::DronesDisabled, burn]);
+        assert!(s.status_effects.is_empty());
+        assert!(!s.drones_disabled());
+    }
+
+    #[test]
+    fn test_status_effects_dodge_penalty_and_charge_rate() {
+        let spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        s.dodge_chance = 15;
+
+        s.apply_status(StatusKind::DodgePenalty { amount: 10 }, 5, &mut |_| {});
+        assert_eq!(s.effective_dodge_chance(), 5);
+        s.apply_status(StatusKind::DodgePenalty { amount: 10 }, 5, &mut |_| {});
+        assert_eq!(s.effective_dodge_chance(), 0);
+
+        s.apply_status(StatusKind::ChargeRate { modifier: 2 }, 5, &mut |_| {});
+        assert_eq!(s.charge_per_turn(), CHARGE_PER_TURN + 2);
+        // a slow can freeze the charge, but never drain it
+        s.apply_status(StatusKind::ChargeRate { modifier: -4 }, 5, &mut |_| {});
+        assert_eq!(s.charge_per_turn(), 0);
+    }
 }
-#[cfg(not(any(test, feature = "render-hooks")))]
-pub enum BattleEvent {}
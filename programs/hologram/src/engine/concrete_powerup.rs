@@ -1,6 +1,9 @@
 use {
-    super::{Effect, PowerUp, PowerupKind},
-    crate::{state::Bonuses, utils::LimitedString},
+    super::{Condition, Effect, PowerUp, PowerupKind},
+    crate::{
+        state::{Bonuses, Trigger},
+        utils::LimitedString,
+    },
 };
 
 // Derived from a power up to be used in the fight engine
@@ -15,8 +18,16 @@ pub struct ConcretePowerup {
     //
     pub heat: u8,
     // what the powerup does
-    pub effect: Effect,
+    pub effect: Option<Effect>,
     pub bonuses: Option<Bonuses>,
+    // reactive powerups are only released by their trigger
+    pub trigger: Option<Trigger>,
+    // point defense powerups attempt to intercept incoming missiles
+    pub intercept_chance: Option<u8>,
+    // player defined rule, the powerup is held once charged until it's met
+    pub gambit: Option<Condition>,
+    // capacitor energy drained on activation
+    pub energy_cost: u8,
     // the base type of the power up for filtering/ui purposes
     pub og_kind: PowerupKind,
     // Pointer to the original powerup
@@ -25,14 +36,24 @@ pub struct ConcretePowerup {
 
 impl ConcretePowerup {
     pub fn new(powerup: Box<dyn PowerUp>) -> Self {
+        let trigger = powerup.get_trigger();
+        let heat = powerup.get_heat().unwrap_or(0);
         Self {
             name: powerup.get_name(),
             accumulated_charge: 0,
             charge_time: powerup.get_charge_time().unwrap_or(0),
             accumulated_heat: 0,
-            heat: powerup.get_heat().unwrap_or(0),
+            // a reaction can't trigger itself endlessly
+            heat: match trigger {
+                Some(_) => heat.max(1),
+                None => heat,
+            },
             effect: powerup.get_effect(),
             bonuses: powerup.get_bonuses(),
+            trigger,
+            intercept_chance: powerup.get_intercept_chance(),
+            gambit: None,
+            energy_cost: powerup.get_energy_cost().unwrap_or(0),
             og_kind: powerup.get_kind(),
             og_powerup: powerup,
         }
@@ -51,6 +72,10 @@ impl ConcretePowerup {
         self.accumulated_heat == 0
     }
 
+    pub fn is_reactive(&self) -> bool {
+        self.trigger.is_some()
+    }
+
     pub fn heat(&mut self) {
         self.accumulated_heat = self.heat;
     }
@@ -62,6 +87,12 @@ impl ConcretePowerup {
         self.activate()
     }
 
+    // charge the module without activating it, it stays ready to fire
+    pub fn charge_and_hold(&mut self, amount: u8) {
+        self.charge(amount);
+        self.accumulated_charge = self.accumulated_charge.min(self.charge_time);
+    }
+
     fn charge(&mut self, amount: u8) {
         self.accumulated_charge = self.accumulated_charge.saturating_add(amount);
     }
@@ -1,17 +1,23 @@
+#[cfg(any(test, feature = "testing"))]
+use core::fmt;
 use {
     super::SpaceShipBattleCard,
-    crate::state::{RepairTarget, Shots, WeaponType},
-    core::fmt,
-    std::sync::Arc,
+    crate::state::{RepairTarget, Shots, StatusKind, StatusTarget, Targeting, WeaponType},
+    anchor_lang::prelude::*,
 };
 
-#[derive(Debug, Clone)]
+// Note: AnchorSerialize/AnchorDeserialize are implemented manually below, the derive macros
+// bound every field type and can't handle recursive types
+#[derive(Debug, Clone, PartialEq)]
 pub enum Effect {
     // shooting at the opponent. Support all weapon type
     Fire {
         damage: u8,
+        // top of the damage range, see WeaponModuleStats
+        max_damage: Option<u8>,
         shots: Shots,
         weapon_type: WeaponType,
+        targeting: Targeting,
     },
     Repair {
         target: RepairTarget,
@@ -20,8 +26,15 @@ pub enum Effect {
     // attempt to disrupt opponent active powerups
     Jam {
         charge_burn: u8,
+        capacitor_drain: u8,
+    },
+    // apply a timed buff or debuff, lasting `duration` turns
+    ApplyStatus {
+        status: StatusKind,
+        target: StatusTarget,
+        duration: u8,
     },
-    
+
     // [Higher order Effects]
     // HO effect representing a chance to apply an effect or do nothing
     Chance {
@@ -35,31 +48,328 @@ pub enum Effect {
         probability1: u8,
         probability2: u8,
     },
-    // HO effect that only happen when the condition function return true
+    // HO effect that only happen when the condition is met
     Conditionnal {
-        condition: ConditionFn,
+        condition: Condition,
         effect: Box<Effect>,
     },
 }
 
-pub struct ConditionFn {
-    pub func: Arc<dyn Fn(&SpaceShipBattleCard) -> bool + Send + Sync>,
+impl Effect {
+    // increase the damage of all shots of the given weapon type, including nested effects
+    pub fn increase_weapon_damage(&mut self, bonus_weapon_type: WeaponType, bonus: u8) {
+        match self {
+            Effect::Fire {
+                damage,
+                max_damage,
+                weapon_type,
+                ..
+            } => {
+                if *weapon_type == bonus_weapon_type {
+                    *damage = damage.saturating_add(bonus);
+                    *max_damage = max_damage.map(|max_damage| max_damage.saturating_add(bonus));
+                }
+            }
+            Effect::Chance { effect, .. } | Effect::Conditionnal { effect, .. } => {
+                effect.increase_weapon_damage(bonus_weapon_type, bonus)
+            }
+            Effect::Composite {
+                effect1, effect2, ..
+            } => {
+                effect1.increase_weapon_damage(bonus_weapon_type, bonus);
+                effect2.increase_weapon_damage(bonus_weapon_type, bonus);
+            }
+            Effect::Repair { .. } | Effect::Jam { .. } | Effect::ApplyStatus { .. } => {}
+        }
+    }
+
+    // Note: weapons always deal at least 1 damage
+    pub fn decrease_weapon_damage(&mut self, malus_weapon_type: WeaponType, malus: u8) {
+        match self {
+            Effect::Fire {
+                damage,
+                max_damage,
+                weapon_type,
+                ..
+            } => {
+                if *weapon_type == malus_weapon_type {
+                    *damage = damage.saturating_sub(malus).max(1);
+                    *max_damage =
+                        max_damage.map(|max_damage| max_damage.saturating_sub(malus).max(1));
+                }
+            }
+            Effect::Chance { effect, .. } | Effect::Conditionnal { effect, .. } => {
+                effect.decrease_weapon_damage(malus_weapon_type, malus)
+            }
+            Effect::Composite {
+                effect1, effect2, ..
+            } => {
+                effect1.decrease_weapon_damage(malus_weapon_type, malus);
+                effect2.decrease_weapon_damage(malus_weapon_type, malus);
+            }
+            Effect::Repair { .. } | Effect::Jam { .. } | Effect::ApplyStatus { .. } => {}
+        }
+    }
+
+    // raise the damage, repair amount and charge burn, including nested effects (see Tier)
+    pub fn increase_potency(&mut self, bonus: u8) {
+        match self {
+            Effect::Fire {
+                damage, max_damage, ..
+            } => {
+                *damage = damage.saturating_add(bonus);
+                *max_damage = max_damage.map(|max_damage| max_damage.saturating_add(bonus));
+            }
+            Effect::Repair { amount, .. } => *amount = amount.saturating_add(bonus),
+            Effect::Jam { charge_burn, .. } => *charge_burn = charge_burn.saturating_add(bonus),
+            Effect::Chance { effect, .. } | Effect::Conditionnal { effect, .. } => {
+                effect.increase_potency(bonus)
+            }
+            Effect::Composite {
+                effect1, effect2, ..
+            } => {
+                effect1.increase_potency(bonus);
+                effect2.increase_potency(bonus);
+            }
+            Effect::ApplyStatus { .. } => {}
+        }
+    }
+
+    // most damage the effect can deal at once (before defenses), including nested effects
+    pub fn potential_damage(&self) -> u8 {
+        match self {
+            Effect::Fire {
+                damage,
+                max_damage,
+                shots,
+                ..
+            } => max_damage
+                .unwrap_or(*damage)
+                .max(*damage)
+                .saturating_mul(shots.count()),
+            Effect::Chance { effect, .. } | Effect::Conditionnal { effect, .. } => {
+                effect.potential_damage()
+            }
+            Effect::Composite {
+                effect1, effect2, ..
+            } => effect1.potential_damage().max(effect2.potential_damage()),
+            Effect::Repair { .. } | Effect::Jam { .. } | Effect::ApplyStatus { .. } => 0,
+        }
+    }
 }
 
-impl Clone for ConditionFn {
-    fn clone(&self) -> Self {
-        Self {
-            func: Arc::clone(&self.func),
+// Conditions are evaluated from the point of view of the spaceship owning the effect
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    // hull damage taken in the last 5 turns
+    RecentHullDamageAtLeast(u8),
+    ShieldDepleted,
+    ArmorDepleted,
+    // hull HP below the given percentage of the max hull HP
+    HullBelowPercent(u8),
+    TurnAtLeast(u16),
+    // at least one of the opponent drones is still flying
+    OpponentHasDrones,
+    Not(Box<Condition>),
+    OpponentShieldDepleted,
+    // one of the opponent active powerups accumulated at least this much charge
+    OpponentChargeAtLeast(u8),
+}
+
+impl Condition {
+    pub fn evaluate(
+        &self,
+        s_origin: &SpaceShipBattleCard,
+        s_target: &SpaceShipBattleCard,
+        turn: u16,
+    ) -> bool {
+        match self {
+            Condition::RecentHullDamageAtLeast(threshold) => {
+                s_origin.recent_hull_damage() >= *threshold
+            }
+            Condition::ShieldDepleted => s_origin.shield_layers.depleted(),
+            Condition::ArmorDepleted => s_origin.armor_hitpoints.depleted(),
+            Condition::HullBelowPercent(percent) => {
+                (s_origin.hull_hitpoints.current as u16) * 100
+                    < (s_origin.hull_hitpoints.max as u16) * (*percent as u16)
+            }
+            Condition::TurnAtLeast(n) => turn >= *n,
+            Condition::OpponentHasDrones => s_target.has_launched_drones(),
+            Condition::Not(condition) => !condition.evaluate(s_origin, s_target, turn),
+            Condition::OpponentShieldDepleted => s_target.shield_layers.depleted(),
+            Condition::OpponentChargeAtLeast(charge) => s_target.highest_charge() >= *charge,
         }
     }
 }
 
-impl fmt::Debug for ConditionFn {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Condition function")
+impl AnchorSerialize for Effect {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            Effect::Fire {
+                damage,
+                max_damage,
+                shots,
+                weapon_type,
+                targeting,
+            } => {
+                0u8.serialize(writer)?;
+                damage.serialize(writer)?;
+                max_damage.serialize(writer)?;
+                shots.serialize(writer)?;
+                weapon_type.serialize(writer)?;
+                targeting.serialize(writer)
+            }
+            Effect::Repair { target, amount } => {
+                1u8.serialize(writer)?;
+                target.serialize(writer)?;
+                amount.serialize(writer)
+            }
+            Effect::Jam {
+                charge_burn,
+                capacitor_drain,
+            } => {
+                2u8.serialize(writer)?;
+                charge_burn.serialize(writer)?;
+                capacitor_drain.serialize(writer)
+            }
+            Effect::ApplyStatus {
+                status,
+                target,
+                duration,
+            } => {
+                3u8.serialize(writer)?;
+                status.serialize(writer)?;
+                target.serialize(writer)?;
+                duration.serialize(writer)
+            }
+            Effect::Chance {
+                probability,
+                effect,
+            } => {
+                4u8.serialize(writer)?;
+                probability.serialize(writer)?;
+                effect.serialize(writer)
+            }
+            Effect::Composite {
+                effect1,
+                effect2,
+                probability1,
+                probability2,
+            } => {
+                5u8.serialize(writer)?;
+                effect1.serialize(writer)?;
+                effect2.serialize(writer)?;
+                probability1.serialize(writer)?;
+                probability2.serialize(writer)
+            }
+            Effect::Conditionnal { condition, effect } => {
+                6u8.serialize(writer)?;
+                condition.serialize(writer)?;
+                effect.serialize(writer)
+            }
+        }
     }
 }
 
+impl AnchorDeserialize for Effect {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let effect = match u8::deserialize_reader(reader)? {
+            0 => Effect::Fire {
+                damage: AnchorDeserialize::deserialize_reader(reader)?,
+                max_damage: AnchorDeserialize::deserialize_reader(reader)?,
+                shots: AnchorDeserialize::deserialize_reader(reader)?,
+                weapon_type: AnchorDeserialize::deserialize_reader(reader)?,
+                targeting: AnchorDeserialize::deserialize_reader(reader)?,
+            },
+            1 => Effect::Repair {
+                target: AnchorDeserialize::deserialize_reader(reader)?,
+                amount: AnchorDeserialize::deserialize_reader(reader)?,
+            },
+            2 => Effect::Jam {
+                charge_burn: AnchorDeserialize::deserialize_reader(reader)?,
+                capacitor_drain: AnchorDeserialize::deserialize_reader(reader)?,
+            },
+            3 => Effect::ApplyStatus {
+                status: AnchorDeserialize::deserialize_reader(reader)?,
+                target: AnchorDeserialize::deserialize_reader(reader)?,
+                duration: AnchorDeserialize::deserialize_reader(reader)?,
+            },
+            4 => Effect::Chance {
+                probability: AnchorDeserialize::deserialize_reader(reader)?,
+                effect: AnchorDeserialize::deserialize_reader(reader)?,
+            },
+            5 => Effect::Composite {
+                effect1: AnchorDeserialize::deserialize_reader(reader)?,
+                effect2: AnchorDeserialize::deserialize_reader(reader)?,
+                probability1: AnchorDeserialize::deserialize_reader(reader)?,
+                probability2: AnchorDeserialize::deserialize_reader(reader)?,
+            },
+            6 => Effect::Conditionnal {
+                condition: AnchorDeserialize::deserialize_reader(reader)?,
+                effect: AnchorDeserialize::deserialize_reader(reader)?,
+            },
+            variant => return Err(invalid_variant_error(variant)),
+        };
+        Ok(effect)
+    }
+}
+
+impl AnchorSerialize for Condition {
+    fn serialize<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            Condition::RecentHullDamageAtLeast(threshold) => {
+                0u8.serialize(writer)?;
+                threshold.serialize(writer)
+            }
+            Condition::ShieldDepleted => 1u8.serialize(writer),
+            Condition::ArmorDepleted => 2u8.serialize(writer),
+            Condition::HullBelowPercent(percent) => {
+                3u8.serialize(writer)?;
+                percent.serialize(writer)
+            }
+            Condition::TurnAtLeast(turn) => {
+                4u8.serialize(writer)?;
+                turn.serialize(writer)
+            }
+            Condition::OpponentHasDrones => 5u8.serialize(writer),
+            Condition::Not(condition) => {
+                6u8.serialize(writer)?;
+                condition.serialize(writer)
+            }
+            Condition::OpponentShieldDepleted => 7u8.serialize(writer),
+            Condition::OpponentChargeAtLeast(charge) => {
+                8u8.serialize(writer)?;
+                charge.serialize(writer)
+            }
+        }
+    }
+}
+
+impl AnchorDeserialize for Condition {
+    fn deserialize_reader<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self> {
+        let condition = match u8::deserialize_reader(reader)? {
+            0 => Condition::RecentHullDamageAtLeast(AnchorDeserialize::deserialize_reader(reader)?),
+            1 => Condition::ShieldDepleted,
+            2 => Condition::ArmorDepleted,
+            3 => Condition::HullBelowPercent(AnchorDeserialize::deserialize_reader(reader)?),
+            4 => Condition::TurnAtLeast(AnchorDeserialize::deserialize_reader(reader)?),
+            5 => Condition::OpponentHasDrones,
+            6 => Condition::Not(AnchorDeserialize::deserialize_reader(reader)?),
+            7 => Condition::OpponentShieldDepleted,
+            8 => Condition::OpponentChargeAtLeast(AnchorDeserialize::deserialize_reader(reader)?),
+            variant => return Err(invalid_variant_error(variant)),
+        };
+        Ok(condition)
+    }
+}
+
+fn invalid_variant_error(variant: u8) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("Unexpected variant index: {:?}", variant),
+    )
+}
+
 #[cfg(any(test, feature = "testing"))]
 impl fmt::Display for Shots {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -75,6 +385,7 @@ impl fmt::Display for RepairTarget {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             RepairTarget::Hull => write!(f, "Hull HP"),
+            RepairTarget::Armor => write!(f, "Armor HP"),
             RepairTarget::Shield => write!(f, "Shield layer"),
         }
     }
@@ -91,3 +402,134 @@ impl fmt::Display for WeaponType {
         }
     }
 }
+
+#[cfg(any(test, feature = "testing"))]
+impl fmt::Display for StatusKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusKind::DronesDisabled => write!(f, "Drones disabled"),
+            StatusKind::Burn { damage } => write!(f, "Burn ({} damage per turn)", damage),
+            StatusKind::DodgePenalty { amount } => write!(f, "Dodge penalty (-{}%)", amount),
+            StatusKind::ChargeRate { modifier } => write!(f, "Charge rate ({:+})", modifier),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{engine::LT_DRONE_COMMON, state::mock_spaceship, utils::LimitedString},
+    };
+
+    #[test]
+    fn test_increase_potency() {
+        let mut effect = Effect::Composite {
+            effect1: Box::new(Effect::Fire {
+                damage: 2,
+                max_damage: Some(4),
+                shots: Shots::Single,
+                weapon_type: WeaponType::Laser,
+                targeting: Targeting::Ship,
+            }),
+            effect2: Box::new(Effect::Chance {
+                probability: 50,
+                effect: Box::new(Effect::Repair {
+                    target: RepairTarget::Hull,
+                    amount: 3,
+                }),
+            }),
+            probability1: 50,
+            probability2: 50,
+        };
+        effect.increase_potency(2);
+
+        let Effect::Composite {
+            effect1, effect2, ..
+        } = effect
+        else {
+            unreachable!()
+        };
+        assert!(matches!(
+            *effect1,
+            Effect::Fire {
+                damage: 4,
+                max_damage: Some(6),
+                ..
+            }
+        ));
+        assert!(matches!(
+            *effect2,
+            Effect::Chance { effect, .. } if matches!(*effect, Effect::Repair { amount: 5, .. })
+        ));
+
+        let mut jam = Effect::Jam {
+            charge_burn: 3,
+            capacitor_drain: 0,
+        };
+        jam.increase_potency(u8::MAX);
+        assert!(matches!(
+            jam,
+            Effect::Jam {
+                charge_burn: u8::MAX,
+                capacitor_drain: 0
+            }
+        ));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let hornet_drone = LT_DRONE_COMMON
+            .into_iter()
+            .find(|d| d.name == LimitedString::new("Hornet"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![], vec![hornet_drone], vec![]);
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let hull_max = s.hull_hitpoints.max;
+
+        assert!(Condition::OpponentHasDrones.evaluate(&s, &os, 0));
+        assert!(!Condition::OpponentHasDrones.evaluate(&os, &s, 0));
+        assert!(Condition::TurnAtLeast(10).evaluate(&s, &os, 10));
+        assert!(!Condition::TurnAtLeast(10).evaluate(&s, &os, 9));
+        assert!(!Condition::HullBelowPercent(50).evaluate(&s, &os, 0));
+        assert!(!Condition::RecentHullDamageAtLeast(1).evaluate(&s, &os, 0));
+
+        s.apply_damage(hull_max / 2 + 1, WeaponType::Plasma, &mut |_| {});
+        assert!(Condition::HullBelowPercent(50).evaluate(&s, &os, 0));
+        assert!(Condition::RecentHullDamageAtLeast(hull_max / 2 + 1).evaluate(&s, &os, 0));
+        assert!(!Condition::Not(Box::new(Condition::ArmorDepleted)).evaluate(&s, &os, 0));
+    }
+
+    #[test]
+    fn test_effect_serialization() {
+        let effect = Effect::Composite {
+            effect1: Box::new(Effect::Conditionnal {
+                condition: Condition::Not(Box::new(Condition::ShieldDepleted)),
+                effect: Box::new(Effect::Fire {
+                    damage: 2,
+                    max_damage: Some(4),
+                    shots: Shots::Salvo(3),
+                    weapon_type: WeaponType::Plasma,
+                    targeting: Targeting::DronesFirst,
+                }),
+            }),
+            effect2: Box::new(Effect::Chance {
+                probability: 30,
+                effect: Box::new(Effect::ApplyStatus {
+                    status: StatusKind::Burn { damage: 1 },
+                    target: StatusTarget::Target,
+                    duration: 3,
+                }),
+            }),
+            probability1: 50,
+            probability2: 50,
+        };
+
+        let serialized = effect.try_to_vec().unwrap();
+        let deserialized = Effect::try_from_slice(&serialized).unwrap();
+        assert_eq!(effect, deserialized);
+        assert!(Effect::try_from_slice(&[42]).is_err());
+    }
+}
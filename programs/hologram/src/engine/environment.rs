@@ -0,0 +1,42 @@
+use {
+    crate::{
+        utils::RandomNumberGenerator, ASTEROID_FIELD_CHANCE, ION_STORM_CHANCE, NEBULA_CHANCE,
+        SOLAR_FLARE_CHANCE,
+    },
+    anchor_lang::prelude::*,
+};
+
+// Where an arena match takes place, applied to both spaceships when the match starts
+// see SpaceShipBattleCard::apply_environment
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Environment {
+    // no effect
+    DeepSpace,
+    // halves the shield layers
+    IonStorm,
+    // boosts the dodge chance
+    Nebula,
+    // weakens missiles
+    AsteroidField,
+    // boosts lasers damage
+    SolarFlare,
+}
+
+impl Environment {
+    // Note: DeepSpace gets the chance left by the others
+    pub fn roll(rng: &mut RandomNumberGenerator) -> Self {
+        let mut roll = rng.roll_dice(100) as u8;
+        for (environment, chance) in [
+            (Environment::IonStorm, ION_STORM_CHANCE),
+            (Environment::Nebula, NEBULA_CHANCE),
+            (Environment::AsteroidField, ASTEROID_FIELD_CHANCE),
+            (Environment::SolarFlare, SOLAR_FLARE_CHANCE),
+        ] {
+            if roll <= chance {
+                return environment;
+            }
+            roll -= chance;
+        }
+        Environment::DeepSpace
+    }
+}
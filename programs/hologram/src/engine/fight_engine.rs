@@ -1,29 +1,151 @@
 use {
-    super::{BattleEvent, ConcretePowerup, Effect, SpaceShipBattleCard},
+    super::{
+        BattleEvent, Effect, Environment, EventStreamHasher, FightLoadout, FightLog,
+        SpaceShipBattleCard, TriggerEvent,
+    },
     crate::{
         instructions::user_facing::Faction,
-        state::{RepairTarget, SpaceShip},
+        state::{RepairTarget, SpaceShip, StatusTarget},
         utils::RandomNumberGenerator,
-        CHARGE_PER_TURN, CURRENCY_REWARD_FOR_ARENA_LOOSER, CURRENCY_REWARD_FOR_ARENA_WINNER,
-        HEAT_DISSIPATION_PER_TURN,
+        CURRENCY_REWARD_FOR_ARENA_LOOSER, CURRENCY_REWARD_FOR_ARENA_WINNER,
+        ESCALATION_RAMP_INTERVAL, ESCALATION_START_TURN, HEAT_DISSIPATION_PER_TURN,
     },
     anchor_lang::prelude::*,
+    std::cmp::Ordering,
 };
 
 pub struct FightEngine {
     event_callback: Box<dyn FnMut(BattleEvent)>,
+    // every event of the last fight, indexed by turn
+    // Note: only recorded off-chain (see set_fight_log_recording), it would quickly exhaust the on-chain heap
+    fight_log: FightLog,
+    records_fight_log: bool,
+    event_stream_hasher: EventStreamHasher,
+    turn: u16,
+    // how each side picks the spaceship its effects are aimed at
+    user_target_selection: TargetSelection,
+    opponent_target_selection: TargetSelection,
+    // None lets stalled fights run until max_turns
+    escalation: Option<Escalation>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
 pub enum FightOutcome {
     UserWon,
     OpponentWon,
     Draw,
 }
 
+// How a fight that didn't produce a winner is decided
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TieBreaker {
+    // hull and armor left, relative to their max
+    HitpointsPercentage,
+    // hitpoints taken from the opposing side
+    DamageDealt,
+}
+
+// Phase forcing stalled fights to an end
+// From start_turn, shields stop regenerating and every spaceship takes increasing hull damage at the end of each turn
+#[derive(Debug, Clone, Copy)]
+pub struct Escalation {
+    pub start_turn: u16,
+    // the attrition damage grows by 1 every ramp_interval turns
+    pub ramp_interval: u16,
+}
+
+impl Default for Escalation {
+    fn default() -> Self {
+        Self {
+            start_turn: ESCALATION_START_TURN,
+            ramp_interval: ESCALATION_RAMP_INTERVAL,
+        }
+    }
+}
+
+impl Escalation {
+    pub fn attrition_damage(&self, turn: u16) -> u8 {
+        let ramp = turn.saturating_sub(self.start_turn) / self.ramp_interval.max(1);
+        ramp.saturating_add(1).min(u8::MAX as u16) as u8
+    }
+}
+
+// How the spaceships of a side pick their target among the opposing spaceships still standing
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum TargetSelection {
+    Random,
+    // the one with the least hull HP left
+    LowestHull,
+    // the one dealing the most damage, see SpaceShipBattleCard::threat
+    HighestThreat,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Side {
+    User,
+    Opponent,
+}
+
+// Result of a match re-simulated off-chain
+#[derive(Debug, Clone)]
+pub struct FightReplay {
+    pub outcome: FightOutcome,
+    pub event_stream_hash: [u8; 32],
+    pub fight_log: FightLog,
+}
+
 impl FightEngine {
     pub fn new(event_callback: Box<dyn FnMut(BattleEvent)>) -> Self {
-        Self { event_callback }
+        Self {
+            event_callback,
+            fight_log: FightLog::default(),
+            records_fight_log: false,
+            event_stream_hasher: EventStreamHasher::default(),
+            turn: 0,
+            user_target_selection: TargetSelection::Random,
+            opponent_target_selection: TargetSelection::Random,
+            escalation: Some(Escalation::default()),
+        }
+    }
+
+    pub fn set_escalation(&mut self, escalation: Option<Escalation>) {
+        self.escalation = escalation;
+    }
+
+    fn is_escalated(&self) -> bool {
+        matches!(self.escalation, Some(escalation) if self.turn >= escalation.start_turn)
+    }
+
+    // Off by default, the settlement only hashes the event stream and the log is rebuilt off-chain with replay
+    pub fn set_fight_log_recording(&mut self, records_fight_log: bool) {
+        self.records_fight_log = records_fight_log;
+    }
+
+    // Number of turns of the last fight played by this engine
+    pub fn turns_played(&self) -> u16 {
+        self.turn
+    }
+
+    // Retrieve the log of the last fight played by this engine, empty unless recording was enabled
+    pub fn take_fight_log(&mut self) -> FightLog {
+        std::mem::take(&mut self.fight_log)
+    }
+
+    // Fingerprint of the events of the last fight played by this engine, see FightReplay
+    pub fn event_stream_hash(&self) -> Result<[u8; 32]> {
+        self.event_stream_hasher.result()
+    }
+
+    // Forward the event to the callback, hash it and record it in the fight log (when enabled)
+    // Note: TurnStart isn't hashed nor recorded as each entry already carries its turn, keeps the log small
+    fn emit(&mut self, event: BattleEvent) {
+        if !matches!(event, BattleEvent::TurnStart { .. }) {
+            self.event_stream_hasher.hash(self.turn, &event);
+            if self.records_fight_log {
+                self.fight_log.record(self.turn, event.clone());
+            }
+        }
+        (self.event_callback)(event);
     }
 
     // This function is used to distribute currency to the winner and looser of an arena match.
@@ -64,117 +186,359 @@ impl FightEngine {
         Ok(())
     }
 
-    // Return true if the spaceship won the fight against opponent_spaceship
+    // Re-run a match from the loadouts of both spaceships at the time of the fight.
+    // The engine is deterministic, so given the inputs reported by ArenaMatchmakingMatchCompleted (loadouts, environment,
+    // seed) and MATCH_MAX_TURN it yields the same outcome and event stream hash, along with the full fight log.
+    pub fn replay(
+        spaceship: &FightLoadout,
+        opponent_spaceship: &FightLoadout,
+        environment: Environment,
+        fight_seed: u32,
+        max_turns: u16,
+    ) -> Result<FightReplay> {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let outcome = fight_engine.fight(
+            &mut SpaceShipBattleCard::from_loadout(spaceship),
+            &mut SpaceShipBattleCard::from_loadout(opponent_spaceship),
+            environment,
+            fight_seed,
+            max_turns,
+        );
+        Ok(FightReplay {
+            outcome,
+            event_stream_hash: fight_engine.event_stream_hash()?,
+            fight_log: fight_engine.take_fight_log(),
+        })
+    }
+
+    // Duel between two spaceships, see fight_fleets
     pub fn fight(
         &mut self,
-        mut user: &mut SpaceShipBattleCard,
-        mut opponent: &mut SpaceShipBattleCard,
+        user: &mut SpaceShipBattleCard,
+        opponent: &mut SpaceShipBattleCard,
+        environment: Environment,
+        fight_seed: u32,
+        max_turns: u16,
+    ) -> FightOutcome {
+        // fleets of one, there is never a target to choose from
+        self.fight_fleets(
+            std::slice::from_mut(user),
+            std::slice::from_mut(opponent),
+            TargetSelection::Random,
+            TargetSelection::Random,
+            environment,
+            fight_seed,
+            max_turns,
+        )
+    }
+
+    // Fight between two sides of any size, a side is defeated once all its spaceships are
+    // The environment alters every spaceship before the first turn
+    // Each effect is aimed at one of the opposing spaceships, picked with the target selection of its side
+    #[allow(clippy::too_many_arguments)]
+    pub fn fight_fleets(
+        &mut self,
+        user_fleet: &mut [SpaceShipBattleCard],
+        opponent_fleet: &mut [SpaceShipBattleCard],
+        user_target_selection: TargetSelection,
+        opponent_target_selection: TargetSelection,
+        environment: Environment,
         fight_seed: u32,
         max_turns: u16,
     ) -> FightOutcome {
-        pub enum SpaceShipType {
-            User,
-            Opponent,
-        }
         let mut rng = RandomNumberGenerator::new(fight_seed as u64);
+        for battlecard in user_fleet.iter_mut().chain(opponent_fleet.iter_mut()) {
+            battlecard.apply_environment(environment);
+        }
+        self.fight_log = if self.records_fight_log {
+            FightLog::new(user_fleet, opponent_fleet, fight_seed)
+        } else {
+            FightLog::default()
+        };
+        self.event_stream_hasher = EventStreamHasher::default();
+        self.turn = 0;
+        self.user_target_selection = user_target_selection;
+        self.opponent_target_selection = opponent_target_selection;
+        let mut user_defeats_reported = vec![false; user_fleet.len()];
+        let mut opponent_defeats_reported = vec![false; opponent_fleet.len()];
 
-        #[cfg(any(test, feature = "render-hooks"))]
-        (self.event_callback)(BattleEvent::MatchStarted {});
+        self.emit(BattleEvent::MatchStarted {});
+        self.emit(BattleEvent::EnvironmentRevealed { environment });
 
-        let mut all_effects_to_apply: Vec<(Effect, usize, SpaceShipType)> = Vec::new();
+        // the effect, it's index in the origin spaceship, the side and index of the origin spaceship
+        let mut all_effects_to_apply: Vec<(Effect, usize, Side, usize)> = Vec::new();
 
-        let mut turn = 0;
-        // will iterate until one of the spaceship is defeated or MATCH_MAX_TURN is reached
-        while turn < max_turns {
-            #[cfg(any(test, feature = "render-hooks"))]
-            (self.event_callback)(BattleEvent::TurnStart { turn });
+        // will iterate until one of the sides is defeated or MATCH_MAX_TURN is reached
+        while self.turn < max_turns {
+            self.emit(BattleEvent::TurnStart { turn: self.turn });
 
-            // stopping condition, a player or both are defeated
-            if user.is_defeated() || opponent.is_defeated() {
+            // stopping condition, a side or both are defeated
+            if is_fleet_defeated(user_fleet) || is_fleet_defeated(opponent_fleet) {
                 break;
             }
+            if self.escalation.map(|e| e.start_turn) == Some(self.turn) {
+                self.emit(BattleEvent::EscalationStarted {});
+            }
 
-            // these vectors will collect all effect that are to be applied to each spaceship
-            // said effects come from active and passive modules
-            //
             // each effect is paired with an index, which represent it's "origin" module in the spaceship's concrete_powerups vector
             // this is used by the game engine for cross interactions between modules and other advanced mechanics
-            let mut user_effects_to_apply = Vec::new();
-            let mut opponent_effects_to_apply = Vec::new();
-
-            // This clone effects, which is not desirable, because they are being mutated in the same scope
-            let collect_effects =
-                |powerups: &mut Vec<ConcretePowerup>,
-                 effects_to_apply: &mut Vec<(Effect, usize)>| {
-                    for (i, p) in powerups.iter_mut().enumerate() {
-                        match p.is_active() {
-                            true => {
-                                if p.charge_and_activate(CHARGE_PER_TURN) {
-                                    effects_to_apply.push((p.effect.clone(), i));
-                                }
-                            }
-                            false => {
-                                p.dissipate_heat(HEAT_DISSIPATION_PER_TURN);
-                                if p.is_off_cooldown() {
-                                    effects_to_apply.push((p.effect.clone(), i));
-                                }
-                            }
-                        }
-                    }
-                };
-            collect_effects(&mut user.concrete_powerups, &mut user_effects_to_apply);
-            collect_effects(
-                &mut opponent.concrete_powerups,
-                &mut opponent_effects_to_apply,
-            );
-
-            // Collect all effects into a single vector: the effect, it's index in the origin spaceship, and an enum value to indicate which spaceship it comes from
             all_effects_to_apply.clear();
-            all_effects_to_apply.extend(
-                user_effects_to_apply
-                    .iter()
-                    .map(|(effect, index)| (effect.clone(), *index, SpaceShipType::User)),
+            Self::collect_fleet_effects(
+                Side::User,
+                user_fleet,
+                opponent_fleet,
+                self.turn,
+                &mut all_effects_to_apply,
             );
-            all_effects_to_apply.extend(
-                opponent_effects_to_apply
-                    .iter()
-                    .map(|(effect, index)| (effect.clone(), *index, SpaceShipType::Opponent)),
+            Self::collect_fleet_effects(
+                Side::Opponent,
+                opponent_fleet,
+                user_fleet,
+                self.turn,
+                &mut all_effects_to_apply,
             );
 
-            // Shuffle the effects
+            // Shuffle the effects, then resolve them by initiative
+            // Note: the sort is stable, spaceships with the same initiative keep the shuffled order
             rng.shuffle(&mut all_effects_to_apply);
+            all_effects_to_apply.sort_by_key(|(_, _, side, ship_index)| {
+                let initiative = match side {
+                    Side::User => user_fleet[*ship_index].initiative,
+                    Side::Opponent => opponent_fleet[*ship_index].initiative,
+                };
+                std::cmp::Reverse(initiative)
+            });
 
             // Apply the effects
-            for (effect, index, spaceship_type) in &all_effects_to_apply {
-                match spaceship_type {
-                    SpaceShipType::User => {
-                        self.apply_effect(effect, *index, &mut user, &mut opponent, &mut rng);
-                    }
-                    SpaceShipType::Opponent => {
-                        self.apply_effect(effect, *index, &mut opponent, &mut user, &mut rng);
+            // Note: a spaceship defeated during the turn still releases the effects it had collected
+            for (effect, index, side, ship_index) in &all_effects_to_apply {
+                let target_selection = self.target_selection(*side);
+                let (origin_fleet, target_fleet) = match side {
+                    Side::User => (&mut *user_fleet, &mut *opponent_fleet),
+                    Side::Opponent => (&mut *opponent_fleet, &mut *user_fleet),
+                };
+                let s_origin = &mut origin_fleet[*ship_index];
+                // shot down earlier this turn
+                if s_origin.is_drone_destroyed(*index) {
+                    continue;
+                }
+                if s_origin.concrete_powerups[*index].is_active() {
+                    self.emit(BattleEvent::PowerUpActivated {
+                        origin_id: s_origin.id,
+                        powerup_index: *index as u8,
+                    });
+                }
+                let target_index = select_target(target_fleet, target_selection, &mut rng);
+                let s_target = &mut target_fleet[target_index];
+                self.apply_effect(effect, *index, s_origin, s_target, &mut rng);
+                self.dispatch_triggers(user_fleet, opponent_fleet, &mut rng);
+            }
+
+            for battlecard in user_fleet.iter_mut().chain(opponent_fleet.iter_mut()) {
+                battlecard.end_of_turn_internals(&mut |event| self.emit(event));
+            }
+            if let Some(escalation) = self.escalation.filter(|_| self.is_escalated()) {
+                let damage = escalation.attrition_damage(self.turn);
+                for battlecard in user_fleet.iter_mut().chain(opponent_fleet.iter_mut()) {
+                    if !battlecard.is_defeated() {
+                        battlecard.apply_attrition_damage(damage, &mut |event| self.emit(event));
                     }
                 }
             }
+            self.dispatch_triggers(user_fleet, opponent_fleet, &mut rng);
 
-            user.end_of_turn_internals();
-            opponent.end_of_turn_internals();
+            self.report_defeats(user_fleet, &mut user_defeats_reported);
+            self.report_defeats(opponent_fleet, &mut opponent_defeats_reported);
 
             // advance turn
-            turn += 1;
+            self.turn += 1;
         }
 
         // define fight outcome
-        let outcome = match (user.is_defeated(), opponent.is_defeated()) {
+        let outcome = match (
+            is_fleet_defeated(user_fleet),
+            is_fleet_defeated(opponent_fleet),
+        ) {
             (true, false) => FightOutcome::OpponentWon,
             (false, true) => FightOutcome::UserWon,
-            _ => FightOutcome::Draw,
+            _ => self.break_tie(user_fleet, opponent_fleet),
         };
 
-        #[cfg(any(test, feature = "render-hooks"))]
-        (self.event_callback)(BattleEvent::MatchEnded { outcome });
+        self.emit(BattleEvent::MatchEnded { outcome });
+        outcome
+    }
+
+    // Decide a fight without winner (both sides defeated or max_turns reached) on the hitpoints left,
+    // then on the damage dealt. Only identical results end in a Draw
+    fn break_tie(
+        &mut self,
+        user_fleet: &[SpaceShipBattleCard],
+        opponent_fleet: &[SpaceShipBattleCard],
+    ) -> FightOutcome {
+        let (user_current, user_max) = fleet_remaining_hitpoints(user_fleet);
+        let (opponent_current, opponent_max) = fleet_remaining_hitpoints(opponent_fleet);
+        // compare current / max ratios without dividing
+        let hitpoints_percentage =
+            (user_current * opponent_max).cmp(&(opponent_current * user_max));
+        let damage_dealt = fleet_damage_taken(opponent_fleet).cmp(&fleet_damage_taken(user_fleet));
+
+        let (tie_breaker, ordering) = match hitpoints_percentage {
+            Ordering::Equal => (TieBreaker::DamageDealt, damage_dealt),
+            _ => (TieBreaker::HitpointsPercentage, hitpoints_percentage),
+        };
+        let outcome = match ordering {
+            Ordering::Greater => FightOutcome::UserWon,
+            Ordering::Less => FightOutcome::OpponentWon,
+            Ordering::Equal => return FightOutcome::Draw,
+        };
+        self.emit(BattleEvent::TieBroken {
+            tie_breaker,
+            outcome,
+        });
         outcome
     }
 
+    fn target_selection(&self, side: Side) -> TargetSelection {
+        match side {
+            Side::User => self.user_target_selection,
+            Side::Opponent => self.opponent_target_selection,
+        }
+    }
+
+    // Collect the effects of the spaceships of a side still in the fight
+    fn collect_fleet_effects(
+        side: Side,
+        fleet: &mut [SpaceShipBattleCard],
+        opponent_fleet: &[SpaceShipBattleCard],
+        turn: u16,
+        all_effects_to_apply: &mut Vec<(Effect, usize, Side, usize)>,
+    ) {
+        for (ship_index, battlecard) in fleet.iter_mut().enumerate() {
+            // defeated spaceships are out of the fight
+            if battlecard.is_defeated() {
+                continue;
+            }
+            let mut effects_to_apply = Vec::new();
+            Self::collect_effects(battlecard, opponent_fleet, turn, &mut effects_to_apply);
+            all_effects_to_apply.extend(
+                effects_to_apply
+                    .into_iter()
+                    .map(|(effect, index)| (effect, index, side, ship_index)),
+            );
+        }
+    }
+
+    // Charge active powerups and cool passive ones, collecting the effects released this turn
+    // This clone effects, which is not desirable, because they are being mutated in the same scope
+    // Note: powerups without effect only provide bonuses/modifiers, nothing is collected for them
+    // Note: an active powerup with a gambit stays charged until the gambit is met against one of the opponents
+    fn collect_effects(
+        battlecard: &mut SpaceShipBattleCard,
+        opponent_fleet: &[SpaceShipBattleCard],
+        turn: u16,
+        effects_to_apply: &mut Vec<(Effect, usize)>,
+    ) {
+        let charge = battlecard.charge_per_turn();
+        for i in 0..battlecard.concrete_powerups.len() {
+            let can_charge = battlecard.can_charge(i);
+            let gambit_met = match &battlecard.concrete_powerups[i].gambit {
+                Some(condition) => opponent_fleet
+                    .iter()
+                    .filter(|o| !o.is_defeated())
+                    .any(|o| condition.evaluate(battlecard, o, turn)),
+                None => true,
+            };
+            let p = &mut battlecard.concrete_powerups[i];
+            match p.is_active() {
+                true => {
+                    if !can_charge {
+                        continue;
+                    }
+                    // without enough energy, the powerup waits charged for the capacitor
+                    if !gambit_met || battlecard.capacitor.current < p.energy_cost {
+                        p.charge_and_hold(charge);
+                    } else if p.charge_and_activate(charge) {
+                        battlecard.capacitor.deplete(p.energy_cost);
+                        effects_to_apply.extend(p.effect.clone().map(|e| (e, i)));
+                    }
+                }
+                false => {
+                    p.dissipate_heat(HEAT_DISSIPATION_PER_TURN);
+                    if p.is_off_cooldown() && !p.is_reactive() {
+                        effects_to_apply.extend(p.effect.clone().map(|e| (e, i)));
+                    }
+                }
+            }
+        }
+    }
+
+    // Announce the spaceships that went down this turn, once
+    fn report_defeats(&mut self, fleet: &[SpaceShipBattleCard], reported: &mut [bool]) {
+        for (battlecard, reported) in fleet.iter().zip(reported.iter_mut()) {
+            if battlecard.is_defeated() && !*reported {
+                *reported = true;
+                self.emit(BattleEvent::SpaceShipDefeated {
+                    origin_id: battlecard.id,
+                });
+            }
+        }
+    }
+
+    // Release the reactive powerups (off cooldown) subscribed to what happened to their spaceship
+    // Reactions can cause other reactions, it stops as reactive powerups heat up when released
+    fn dispatch_triggers(
+        &mut self,
+        user_fleet: &mut [SpaceShipBattleCard],
+        opponent_fleet: &mut [SpaceShipBattleCard],
+        rng: &mut RandomNumberGenerator,
+    ) {
+        loop {
+            // user side first, in fleet order
+            let pending = |fleet: &[SpaceShipBattleCard]| {
+                fleet.iter().position(|s| !s.pending_triggers.is_empty())
+            };
+            let (side, ship_index) = if let Some(ship_index) = pending(user_fleet) {
+                (Side::User, ship_index)
+            } else if let Some(ship_index) = pending(opponent_fleet) {
+                (Side::Opponent, ship_index)
+            } else {
+                break;
+            };
+            let target_selection = self.target_selection(side);
+            let (origin_fleet, target_fleet) = match side {
+                Side::User => (&mut *user_fleet, &mut *opponent_fleet),
+                Side::Opponent => (&mut *opponent_fleet, &mut *user_fleet),
+            };
+            let s_origin = &mut origin_fleet[ship_index];
+            let event = s_origin.pending_triggers.remove(0);
+
+            for index in 0..s_origin.concrete_powerups.len() {
+                let powerup = &s_origin.concrete_powerups[index];
+                let (Some(trigger), Some(effect)) = (powerup.trigger, powerup.effect.clone())
+                else {
+                    continue;
+                };
+                if !powerup.is_off_cooldown() || !s_origin.reacts_to(trigger, event) {
+                    continue;
+                }
+                self.emit(BattleEvent::PowerUpReacted {
+                    origin_id: s_origin.id,
+                    powerup_index: index as u8,
+                    trigger,
+                });
+                let target_index = select_target(target_fleet, target_selection, rng);
+                self.apply_effect(
+                    &effect,
+                    index,
+                    s_origin,
+                    &mut target_fleet[target_index],
+                    rng,
+                );
+            }
+        }
+    }
+
     // apply an effect to a spaceship
     // return wether something happened or not (in case of chance based and conditionnal effects)
     fn apply_effect(
@@ -189,34 +553,65 @@ impl FightEngine {
         let effect_triggered = match effect {
             Effect::Fire {
                 damage,
+                max_damage,
                 shots,
                 weapon_type,
+                targeting,
             } => {
                 s_origin.fire_at(
                     s_target,
                     rng,
                     *damage,
+                    *max_damage,
                     *shots,
                     *weapon_type,
-                    &mut self.event_callback,
+                    *targeting,
+                    &mut |event| self.emit(event),
                 );
                 true
             }
+            // shields don't regenerate anymore once the fight escalates
+            Effect::Repair {
+                target: RepairTarget::Shield,
+                ..
+            } if self.is_escalated() => false,
             Effect::Repair { target, amount } => {
-                #[cfg(any(test, feature = "render-hooks"))]
-                (self.event_callback)(BattleEvent::Repair {
+                self.emit(BattleEvent::Repair {
                     origin_id: s_origin.id,
                     repair_target: *target,
                     amount: *amount,
                 });
+                s_target.pending_triggers.push(TriggerEvent::OpponentRepair);
                 match target {
                     RepairTarget::Hull => s_origin.hull_hitpoints.resplenish(*amount),
+                    RepairTarget::Armor => s_origin.armor_hitpoints.resplenish_up_to_max(*amount),
                     RepairTarget::Shield => s_origin.shield_layers.resplenish(*amount),
                 };
                 true
             }
-            Effect::Jam { charge_burn } => {
-                s_origin.jam(s_target, rng, *charge_burn, &mut self.event_callback);
+            Effect::Jam {
+                charge_burn,
+                capacitor_drain,
+            } => {
+                s_origin.jam(
+                    s_target,
+                    rng,
+                    *charge_burn,
+                    *capacitor_drain,
+                    &mut |event| self.emit(event),
+                );
+                true
+            }
+            Effect::ApplyStatus {
+                status,
+                target,
+                duration,
+            } => {
+                let affected = match target {
+                    StatusTarget::Origin => &mut *s_origin,
+                    StatusTarget::Target => &mut *s_target,
+                };
+                affected.apply_status(*status, *duration, &mut |event| self.emit(event));
                 true
             }
             Effect::Chance {
@@ -247,7 +642,7 @@ impl FightEngine {
                 true
             }
             Effect::Conditionnal { condition, effect } => {
-                if (condition.func)(s_origin) {
+                if condition.evaluate(s_origin, s_target, self.turn) {
                     self.apply_effect(effect, source_powerup_index, s_origin, s_target, rng)
                 } else {
                     false
@@ -256,26 +651,91 @@ impl FightEngine {
         };
 
         // if it's a passive and it was triggered, heat it (put it in cooldown period)
+        // Note: nested effects trigger their parent as well, only the first one is reported
         if effect_triggered {
+            let origin_id = s_origin.id;
             let source_powerup = &mut s_origin.concrete_powerups[source_powerup_index];
-            if !source_powerup.is_active() {
+            if !source_powerup.is_active() && source_powerup.is_off_cooldown() {
                 source_powerup.heat();
+                self.emit(BattleEvent::PassiveTriggered {
+                    origin_id,
+                    powerup_index: source_powerup_index as u8,
+                });
             }
         }
         effect_triggered
     }
 }
 
+fn is_fleet_defeated(fleet: &[SpaceShipBattleCard]) -> bool {
+    fleet.iter().all(|s| s.is_defeated())
+}
+
+fn fleet_remaining_hitpoints(fleet: &[SpaceShipBattleCard]) -> (u32, u32) {
+    fleet
+        .iter()
+        .map(|s| s.remaining_hitpoints())
+        .fold((0, 0), |(current, max), (c, m)| {
+            (current + c as u32, max + m as u32)
+        })
+}
+
+fn fleet_damage_taken(fleet: &[SpaceShipBattleCard]) -> u32 {
+    fleet.iter().map(|s| s.damage_taken as u32).sum()
+}
+
+// Index of the spaceship an effect is aimed at
+// Once the whole fleet is defeated effects keep resolving against it until the end of the turn, as in a duel
+// Note: only consumes randomness when there is an actual choice to make
+fn select_target(
+    fleet: &[SpaceShipBattleCard],
+    target_selection: TargetSelection,
+    rng: &mut RandomNumberGenerator,
+) -> usize {
+    let mut candidates: Vec<usize> = (0..fleet.len())
+        .filter(|&i| !fleet[i].is_defeated())
+        .collect();
+    if candidates.is_empty() {
+        candidates = (0..fleet.len()).collect();
+    }
+    if candidates.len() == 1 {
+        return candidates[0];
+    }
+    // ties go to the first spaceship of the fleet
+    match target_selection {
+        TargetSelection::Random => candidates[rng.roll_dice(candidates.len()) as usize - 1],
+        TargetSelection::LowestHull => *candidates
+            .iter()
+            .min_by_key(|&&i| fleet[i].hull_hitpoints.current)
+            .unwrap(),
+        TargetSelection::HighestThreat => *candidates
+            .iter()
+            .min_by_key(|&&i| std::cmp::Reverse(fleet[i].threat()))
+            .unwrap(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
         super::*,
         crate::{
-            engine::{PowerUp, LT_MODULES_COMMON, LT_MODULES_RARE, LT_MODULES_UNCOMMON},
+            engine::Condition,
+            engine::{
+                PowerUp, LT_DRONE_COMMON, LT_DRONE_OFFENSIVE_FACTION, LT_MODULES_COMMON,
+                LT_MODULES_RARE, LT_MODULES_UNCOMMON, LT_MUTATIONS_RARE, LT_MUTATIONS_UNCOMMON,
+            },
             instructions::print_event,
-            state::{mock_spaceship, ModuleClass},
+            state::{
+                mock_spaceship, Gambit, HitPoints, Hull, Module, ModuleClass, PowerUpType, Trigger,
+            },
             utils::LimitedString,
-            MATCH_MAX_TURN,
+            ASTEROID_FIELD_MISSILE_DAMAGE_MALUS, BASE_CAPACITOR_CAPACITY,
+            BASE_CAPACITOR_RECHARGE_PER_TURN, BASE_CRITICAL_HIT_CHANCE,
+            BASE_CRITICAL_HIT_MULTIPLIER, BASE_HULL_HITPOINTS, BASE_INITIATIVE,
+            CRITICAL_HIT_CHANCE_CAP, CRITICAL_HIT_MULTIPLIER_CAP, ESCALATION_START_TURN,
+            INITIATIVE_CAP, MATCH_MAX_TURN, NEBULA_DODGE_CHANCE_BONUS,
+            SOLAR_FLARE_LASER_DAMAGE_BONUS,
         },
     };
 
@@ -288,7 +748,13 @@ mod tests {
 
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
-        let outcome = fight_engine.fight(&mut s, &mut os, fight_seed, MATCH_MAX_TURN);
+        let outcome = fight_engine.fight(
+            &mut s,
+            &mut os,
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
 
         assert!(matches!(outcome, FightOutcome::Draw));
     }
@@ -312,7 +778,7 @@ mod tests {
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
         let turns = 18;
-        let _ = fight_engine.fight(&mut s, &mut os, fight_seed, turns);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
 
         assert_eq!(os.shield_layers.current, os.shield_layers.max);
         assert_eq!(os.hull_hitpoints.current, os.hull_hitpoints.max);
@@ -337,7 +803,7 @@ mod tests {
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
         let turns = 10;
-        let _ = fight_engine.fight(&mut s, &mut os, fight_seed, turns);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
 
         assert_eq!(os.shield_layers.current, os.shield_layers.max - 1);
         assert_eq!(os.hull_hitpoints.current, os.hull_hitpoints.max);
@@ -356,7 +822,13 @@ mod tests {
 
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
-        let outcome = fight_engine.fight(&mut s, &mut os, fight_seed, MATCH_MAX_TURN);
+        let outcome = fight_engine.fight(
+            &mut s,
+            &mut os,
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
 
         assert!(matches!(outcome, FightOutcome::UserWon));
     }
@@ -374,7 +846,13 @@ mod tests {
 
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
-        let outcome = fight_engine.fight(&mut s, &mut os, fight_seed, MATCH_MAX_TURN);
+        let outcome = fight_engine.fight(
+            &mut s,
+            &mut os,
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
 
         assert!(matches!(outcome, FightOutcome::UserWon));
     }
@@ -392,7 +870,13 @@ mod tests {
 
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
-        let outcome = fight_engine.fight(&mut s, &mut os, fight_seed, MATCH_MAX_TURN);
+        let outcome = fight_engine.fight(
+            &mut s,
+            &mut os,
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
 
         assert!(matches!(outcome, FightOutcome::UserWon));
     }
@@ -427,8 +911,10 @@ mod tests {
 
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        // all lasers fire in the same turn, whatever their energy cost
+        os.capacitor = HitPoints::init(u8::MAX);
         let turns = 17;
-        let _ = fight_engine.fight(&mut s, &mut os, fight_seed, turns);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
 
         assert_eq!(s.shield_layers.current, s.shield_layers.max);
     }
@@ -461,7 +947,7 @@ mod tests {
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
         let turns = 18;
-        let _ = fight_engine.fight(&mut s, &mut os, fight_seed, turns);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
 
         // minus damages + heal
         assert_eq!(s.hull_hitpoints.current, s.hull_hitpoints.max - 8 + 2);
@@ -492,7 +978,7 @@ mod tests {
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
         let turns = 15;
-        let _ = fight_engine.fight(&mut s, &mut os, fight_seed, turns);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
 
         assert_ne!(
             s.concrete_powerups.first().unwrap().accumulated_charge,
@@ -525,7 +1011,7 @@ mod tests {
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
         let turns = 15;
-        let _ = fight_engine.fight(&mut s, &mut os, fight_seed, turns);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
 
         // -1 cause we are still at turn 15 here
         assert_eq!(
@@ -534,6 +1020,159 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fight_log() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Light Missile Launcher I"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let fight_seed = 1;
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let outcome = fight_engine.fight(
+            &mut s,
+            &mut os,
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
+        let fight_log = fight_engine.take_fight_log();
+
+        assert!(matches!(outcome, FightOutcome::UserWon));
+        assert_eq!(fight_log.seed, fight_seed);
+        assert_eq!(fight_log.user[0].powerups, vec!["Light Missile Launcher I"]);
+        assert_eq!(
+            fight_log.opponent[0].hull_hitpoints.current,
+            os.hull_hitpoints.max
+        );
+        assert!(matches!(
+            fight_log.entries.first().unwrap().event,
+            BattleEvent::MatchStarted {}
+        ));
+        assert!(matches!(
+            fight_log.entries.last().unwrap().event,
+            BattleEvent::MatchEnded {
+                outcome: FightOutcome::UserWon
+            }
+        ));
+        assert!(fight_log.entries.iter().any(|e| matches!(
+            e.event,
+            BattleEvent::PowerUpActivated {
+                powerup_index: 0,
+                ..
+            }
+        )));
+
+        // the log survives a serialization round trip
+        let bytes = fight_log.try_to_vec().unwrap();
+        let deserialized_fight_log = FightLog::try_from_slice(&bytes).unwrap();
+        assert_eq!(
+            deserialized_fight_log.entries.len(),
+            fight_log.entries.len()
+        );
+
+        // on-chain the log isn't recorded, the event stream is still hashed
+        let event_stream_hash = fight_engine.event_stream_hash().unwrap();
+        fight_engine.set_fight_log_recording(false);
+        fight_engine.fight(
+            &mut SpaceShipBattleCard::new(&spaceship),
+            &mut SpaceShipBattleCard::new(&opponent_spaceship),
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
+        assert!(fight_engine.take_fight_log().entries.is_empty());
+        assert_eq!(fight_engine.event_stream_hash().unwrap(), event_stream_hash);
+    }
+
+    #[test]
+    fn test_fight_replay() {
+        let module = LT_MODULES_UNCOMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("125mm Dual Autocannon"))
+            .unwrap();
+        let opponent_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![opponent_module], vec![], vec![]);
+        let fight_seed = 3;
+
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        let outcome = fight_engine.fight(
+            &mut SpaceShipBattleCard::new(&spaceship),
+            &mut SpaceShipBattleCard::new(&opponent_spaceship),
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
+        let event_stream_hash = fight_engine.event_stream_hash().unwrap();
+
+        let replay = FightEngine::replay(
+            &FightLoadout::new(&spaceship),
+            &FightLoadout::new(&opponent_spaceship),
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        )
+        .unwrap();
+        assert_eq!(outcome, replay.outcome);
+        assert_eq!(replay.event_stream_hash, event_stream_hash);
+
+        // the running hash covers the same bytes as the serialized log entries
+        let mut hasher = anchor_lang::solana_program::hash::Hasher::default();
+        for entry in replay.fight_log.entries.iter() {
+            hasher.hash(&entry.try_to_vec().unwrap());
+        }
+        assert_eq!(hasher.result().to_bytes(), event_stream_hash);
+
+        // another seed yields the same hash only if it played out the exact same match
+        let other_replay = FightEngine::replay(
+            &FightLoadout::new(&spaceship),
+            &FightLoadout::new(&opponent_spaceship),
+            Environment::DeepSpace,
+            fight_seed + 1,
+            MATCH_MAX_TURN,
+        )
+        .unwrap();
+        assert_eq!(
+            replay.event_stream_hash == other_replay.event_stream_hash,
+            replay.fight_log.entries.try_to_vec().unwrap()
+                == other_replay.fight_log.entries.try_to_vec().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fight_armor_repair() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        let armor_repairer_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Small Armor Repairer I"))
+            .unwrap();
+        let missile_launcher_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Light Missile Launcher I"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![armor_repairer_module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![missile_launcher_module], vec![], vec![]);
+        let fight_seed = 1;
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let turns = 28;
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
+
+        // the armor soaked the missile (-2) and was repaired afterward (+2)
+        assert_eq!(s.armor_hitpoints.current, s.armor_hitpoints.max);
+        assert_eq!(s.hull_hitpoints.current, s.hull_hitpoints.max);
+    }
+
     #[test]
     fn test_fight_jam_module_nothing_to_jam() {
         let mut fight_engine = FightEngine::new(Box::new(|e| print_event(e)));
@@ -548,6 +1187,726 @@ mod tests {
         let mut s = SpaceShipBattleCard::new(&spaceship);
         let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
         let turns = MATCH_MAX_TURN;
-        let _ = fight_engine.fight(&mut s, &mut os, fight_seed, turns);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
+    }
+
+    #[test]
+    fn test_fight_em_smartbomb_disables_drones() {
+        let mut fight_engine = FightEngine::new(Box::new(|e| print_event(e)));
+        let em_smartbomb_module = LT_MODULES_UNCOMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("EM Smartbomb"))
+            .unwrap();
+        let hornet_drone = LT_DRONE_COMMON
+            .into_iter()
+            .find(|d| d.name == LimitedString::new("Hornet"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![em_smartbomb_module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![], vec![hornet_drone], vec![]);
+        let fight_seed = 1;
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let turns = 24;
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
+
+        // the smartbomb goes off on turn 19, the drone doesn't charge during turns 20 to 22
+        assert!(os.status_effects.is_empty());
+        assert_eq!(os.concrete_powerups.first().unwrap().accumulated_charge, 1);
+    }
+
+    #[test]
+    fn test_fight_flak_shoots_drones_down() {
+        let mut fight_engine = FightEngine::new(Box::new(|e| print_event(e)));
+        let flak_cannon_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Flak Cannon"))
+            .unwrap();
+        let hornet_drone = LT_DRONE_COMMON
+            .into_iter()
+            .find(|d| d.name == LimitedString::new("Hornet"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![flak_cannon_module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![], vec![hornet_drone], vec![]);
+        let fight_seed = 1;
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        assert!(os.has_launched_drones());
+        let turns = 20;
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
+
+        // the flak salvo on turn 11 takes the 2 HP of the hornet, which never fires again
+        assert!(!os.has_launched_drones());
+        assert!(os.is_drone_destroyed(0));
+        assert_eq!(os.concrete_powerups[0].accumulated_charge, 0);
+        // shots went to the drone only
+        assert_eq!(os.hull_hitpoints.current, os.hull_hitpoints.max);
+    }
+
+    #[test]
+    fn test_gambits_hold_powerups() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let hornet_drone = LT_DRONE_COMMON[0].clone();
+        let mut spaceship = mock_spaceship(
+            vec![pulse_laser_module.clone(), pulse_laser_module],
+            vec![hornet_drone],
+            vec![],
+        );
+        let opponent_spaceship = mock_spaceship(vec![], vec![], vec![]);
+
+        // only active power-ups, once each
+        let hold_until = |index: u8, turn: u16| Gambit {
+            powerup_type: PowerUpType::Module,
+            index,
+            condition: Condition::TurnAtLeast(turn),
+        };
+        assert!(spaceship.set_gambits(vec![hold_until(2, 40)]).is_err());
+        assert!(spaceship
+            .set_gambits(vec![hold_until(1, 40), hold_until(1, 50)])
+            .is_err());
+        spaceship
+            .set_gambits(vec![hold_until(0, 1), hold_until(1, 40)])
+            .unwrap();
+        // the gambit follows its module when the fitting changes
+        spaceship.unequip(PowerUpType::Module, 0).unwrap();
+        assert_eq!(spaceship.gambits, vec![hold_until(0, 40)]);
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let charge_time = s.concrete_powerups[0].charge_time;
+        let _ = fight_engine.fight(
+            &mut s,
+            &mut os,
+            Environment::DeepSpace,
+            1,
+            40 + charge_time as u16 - 1,
+        );
+        let fight_log = fight_engine.take_fight_log();
+
+        // the laser is held charged, then fires once when released
+        let activations: Vec<u16> = fight_log
+            .entries
+            .iter()
+            .filter(|e| {
+                matches!(
+                    e.event,
+                    BattleEvent::PowerUpActivated {
+                        powerup_index: 0,
+                        ..
+                    }
+                )
+            })
+            .map(|e| e.turn)
+            .collect();
+        assert_eq!(activations, vec![40]);
+    }
+
+    #[test]
+    fn test_capacitor_limits_activations() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let heavy_pulse_laser_module = LT_MODULES_UNCOMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Heavy Pulse Laser"))
+            .unwrap();
+        let capacitor_battery_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Capacitor Battery"))
+            .unwrap();
+        let capacitor_power_relay_module = LT_MODULES_UNCOMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Capacitor Power Relay"))
+            .unwrap();
+        let energy_neutralizer_module = LT_MODULES_UNCOMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Energy Neutralizer"))
+            .unwrap();
+
+        let s = SpaceShipBattleCard::new(&mock_spaceship(
+            vec![capacitor_battery_module, capacitor_power_relay_module],
+            vec![],
+            vec![],
+        ));
+        assert_eq!(s.capacitor.max, BASE_CAPACITOR_CAPACITY + 10);
+        assert_eq!(
+            s.capacitor_recharge_per_turn,
+            BASE_CAPACITOR_RECHARGE_PER_TURN + 1
+        );
+
+        // all lasers are charged on the same turn, but the capacitor can't power them all
+        let spaceship = mock_spaceship(vec![heavy_pulse_laser_module; 8], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let charge_time = s.concrete_powerups[0].charge_time as u16;
+        let energy_cost = s.concrete_powerups[0].energy_cost;
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, 1, charge_time);
+        let fight_log = fight_engine.take_fight_log();
+        let activations = fight_log
+            .entries
+            .iter()
+            .filter(|e| matches!(e.event, BattleEvent::PowerUpActivated { .. }))
+            .count();
+        assert_eq!(
+            activations,
+            (BASE_CAPACITOR_CAPACITY / energy_cost) as usize
+        );
+
+        // neutralizers drain the opponent capacitor
+        let spaceship = mock_spaceship(vec![energy_neutralizer_module], vec![], vec![]);
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        os.jamming_nullifying_chance = 0;
+        let charge_time = s.concrete_powerups[0].charge_time as u16;
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, 1, charge_time);
+        let fight_log = fight_engine.take_fight_log();
+        assert!(fight_log
+            .entries
+            .iter()
+            .any(|e| matches!(e.event, BattleEvent::CapacitorDrained { amount: 8, .. })));
+        assert!(os.capacitor.current < os.capacitor.max);
+    }
+
+    #[test]
+    fn test_critical_hits_and_damage_variance() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let scattershot_module = LT_MODULES_RARE
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("'Scattershot' Autocannon"))
+            .unwrap();
+        let fire_control_array_module = LT_MODULES_RARE
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Fire Control Array"))
+            .unwrap();
+
+        let s = SpaceShipBattleCard::new(&mock_spaceship(
+            vec![fire_control_array_module.clone()],
+            vec![],
+            vec![],
+        ));
+        assert_eq!(s.critical_hit_chance, BASE_CRITICAL_HIT_CHANCE + 10);
+        assert_eq!(s.critical_hit_multiplier, BASE_CRITICAL_HIT_MULTIPLIER + 50);
+
+        // bonuses are capped
+        let spaceship = mock_spaceship(
+            [vec![scattershot_module], vec![fire_control_array_module; 5]].concat(),
+            vec![],
+            vec![],
+        );
+        let s = SpaceShipBattleCard::new(&spaceship);
+        assert_eq!(s.critical_hit_chance, CRITICAL_HIT_CHANCE_CAP);
+        assert_eq!(s.critical_hit_multiplier, CRITICAL_HIT_MULTIPLIER_CAP);
+
+        // the same seed rolls the same shots
+        let opponent_spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut fight_logs = vec![];
+        for _ in 0..2 {
+            let mut s = SpaceShipBattleCard::new(&spaceship);
+            let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+            os.dodge_chance = 0;
+            let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, 42, MATCH_MAX_TURN);
+            fight_logs.push(fight_engine.take_fight_log());
+        }
+        assert_eq!(
+            format!("{:?}", fight_logs[0].entries),
+            format!("{:?}", fight_logs[1].entries)
+        );
+        let critical_hits = fight_logs[0]
+            .entries
+            .iter()
+            .filter_map(|e| match e.event {
+                BattleEvent::CriticalHit { damage, .. } => Some(damage),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(!critical_hits.is_empty());
+        assert!(critical_hits
+            .iter()
+            .all(|&damage| (2..=10).contains(&damage)));
+    }
+
+    #[test]
+    fn test_point_defense_intercepts_missiles() {
+        let mut fight_engine = FightEngine::new(Box::new(|e| print_event(e)));
+        fight_engine.set_fight_log_recording(true);
+        let missile_launcher_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Light Missile Launcher I"))
+            .unwrap();
+        let prophet_drone = LT_DRONE_OFFENSIVE_FACTION[0].clone();
+        let mut point_defense_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Point Defense Turret"))
+            .unwrap();
+        if let ModuleClass::PointDefense(_, pds) = &mut point_defense_module.class {
+            pds.intercept_chance = 100;
+        }
+        let spaceship = mock_spaceship(vec![missile_launcher_module], vec![prophet_drone], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![point_defense_module], vec![], vec![]);
+        let fight_seed = 1;
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let turns = 35;
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
+
+        // the launcher missile (turn 18) is intercepted, the turret then heats up on the
+        // first missile of the prophet salvo (turn 29) and lets the 2 others through
+        let intercepts = fight_engine
+            .take_fight_log()
+            .entries
+            .iter()
+            .filter(|e| matches!(e.event, BattleEvent::MissileIntercepted { .. }))
+            .count();
+        assert_eq!(intercepts, 2);
+        assert_eq!(os.hull_hitpoints.current, os.hull_hitpoints.max - 4);
+    }
+
+    #[test]
+    fn test_fleet_fight() {
+        let mut fight_engine = FightEngine::new(Box::new(|e| print_event(e)));
+        fight_engine.set_fight_log_recording(true);
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let fleet = |first_id: u64, modules: Vec<Module>| {
+            (0..2)
+                .map(|i| {
+                    let mut battlecard =
+                        SpaceShipBattleCard::new(&mock_spaceship(modules.clone(), vec![], vec![]));
+                    battlecard.id = first_id + i;
+                    battlecard
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut user_fleet = fleet(1, vec![pulse_laser_module]);
+        let mut opponent_fleet = fleet(3, vec![]);
+        let fight_seed = 1;
+        // the pulse lasers are slow, attrition would get everyone
+        fight_engine.set_escalation(None);
+
+        let outcome = fight_engine.fight_fleets(
+            &mut user_fleet,
+            &mut opponent_fleet,
+            TargetSelection::LowestHull,
+            TargetSelection::Random,
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
+
+        assert_eq!(outcome, FightOutcome::UserWon);
+        assert!(opponent_fleet.iter().all(|s| s.is_defeated()));
+        let fight_log = fight_engine.take_fight_log();
+        assert_eq!(fight_log.opponent.len(), 2);
+        let defeated: Vec<u64> = fight_log
+            .entries
+            .iter()
+            .filter_map(|e| match e.event {
+                BattleEvent::SpaceShipDefeated { origin_id } => Some(origin_id),
+                _ => None,
+            })
+            .collect();
+        // focus fire takes the spaceships down one after the other
+        assert_eq!(defeated, vec![3, 4]);
+    }
+
+    #[test]
+    fn test_select_target() {
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let mut fleet = vec![
+            SpaceShipBattleCard::new(&mock_spaceship(vec![], vec![], vec![])),
+            SpaceShipBattleCard::new(&mock_spaceship(vec![pulse_laser_module], vec![], vec![])),
+            SpaceShipBattleCard::new(&mock_spaceship(vec![], vec![], vec![])),
+        ];
+        let mut rng = RandomNumberGenerator::new(1);
+
+        fleet[2].hull_hitpoints.deplete(5);
+        assert_eq!(
+            select_target(&fleet, TargetSelection::LowestHull, &mut rng),
+            2
+        );
+        assert_eq!(
+            select_target(&fleet, TargetSelection::HighestThreat, &mut rng),
+            1
+        );
+
+        // defeated spaceships aren't targeted anymore, unless the whole fleet is
+        fleet[1].hull_hitpoints.deplete(u8::MAX);
+        assert_eq!(
+            select_target(&fleet, TargetSelection::LowestHull, &mut rng),
+            2
+        );
+        fleet[1].hull_hitpoints.resplenish(1);
+        fleet[0].hull_hitpoints.deplete(u8::MAX);
+        fleet[2].hull_hitpoints.deplete(u8::MAX);
+        assert_eq!(select_target(&fleet, TargetSelection::Random, &mut rng), 1);
+        fleet[1].hull_hitpoints.deplete(u8::MAX);
+        assert_eq!(
+            select_target(&fleet, TargetSelection::HighestThreat, &mut rng),
+            1
+        );
+    }
+
+    #[test]
+    fn test_escalation_ends_stalled_fights() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let armor_repairer_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Small Armor Repairer I"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![armor_repairer_module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let fight_seed = 1;
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let outcome = fight_engine.fight(
+            &mut s,
+            &mut os,
+            Environment::DeepSpace,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
+        let fight_log = fight_engine.take_fight_log();
+
+        // nobody can deal damage, attrition takes both hulls down on the same turn
+        assert!(s.is_defeated() && os.is_defeated());
+        assert!(fight_log.entries.last().unwrap().turn < ESCALATION_START_TURN + 30);
+        // the armor left decides
+        assert_eq!(outcome, FightOutcome::UserWon);
+        assert!(fight_log.entries.iter().any(|e| matches!(
+            e.event,
+            BattleEvent::TieBroken {
+                tie_breaker: TieBreaker::HitpointsPercentage,
+                outcome: FightOutcome::UserWon,
+            }
+        )));
+    }
+
+    #[test]
+    fn test_escalation_attrition_damage() {
+        let escalation = Escalation {
+            start_turn: 100,
+            ramp_interval: 10,
+        };
+        assert_eq!(escalation.attrition_damage(100), 1);
+        assert_eq!(escalation.attrition_damage(109), 1);
+        assert_eq!(escalation.attrition_damage(110), 2);
+        assert_eq!(escalation.attrition_damage(u16::MAX), u8::MAX);
+    }
+
+    #[test]
+    fn test_initiative_orders_effects() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let afterburner_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("1MN Afterburner"))
+            .unwrap();
+
+        let mut spaceship = mock_spaceship(
+            vec![pulse_laser_module.clone(), afterburner_module.clone()],
+            vec![],
+            vec![],
+        );
+        spaceship.id = 1;
+        let s = SpaceShipBattleCard::new(&spaceship);
+        assert_eq!(s.initiative, BASE_INITIATIVE + 5);
+        assert_eq!(s.hull_hitpoints.max, BASE_HULL_HITPOINTS - 3);
+
+        spaceship.hull = Hull::UncommonThree;
+        let s = SpaceShipBattleCard::new(&spaceship);
+        assert_eq!(s.initiative, Hull::UncommonThree.profile().initiative + 5);
+
+        // bonuses stack up to the cap
+        spaceship.modules = vec![afterburner_module.clone(); 12];
+        let s = SpaceShipBattleCard::new(&spaceship);
+        assert_eq!(s.initiative, INITIATIVE_CAP);
+        assert_eq!(s.hull_hitpoints.max, 1);
+
+        // the faster spaceship always resolves its effects first
+        let mut spaceship = mock_spaceship(
+            vec![pulse_laser_module.clone(), afterburner_module],
+            vec![],
+            vec![],
+        );
+        spaceship.id = 1;
+        let opponent_spaceship = mock_spaceship(vec![pulse_laser_module], vec![], vec![]);
+        for fight_seed in 0..10 {
+            let mut s = SpaceShipBattleCard::new(&spaceship);
+            let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+            let charge_time = s.concrete_powerups[0].charge_time as u16;
+            let _ = fight_engine.fight(
+                &mut s,
+                &mut os,
+                Environment::DeepSpace,
+                fight_seed,
+                charge_time,
+            );
+            let fight_log = fight_engine.take_fight_log();
+            let activations = fight_log
+                .entries
+                .iter()
+                .filter_map(|e| match e.event {
+                    BattleEvent::PowerUpActivated { origin_id, .. } => Some(origin_id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(activations, vec![1, 0]);
+        }
+    }
+
+    #[test]
+    fn test_environments() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let missile_launcher_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Light Missile Launcher I"))
+            .unwrap();
+        let mut spaceship = mock_spaceship(
+            vec![pulse_laser_module, missile_launcher_module],
+            vec![],
+            vec![],
+        );
+        spaceship.hull = Hull::RareTwo;
+        let base = SpaceShipBattleCard::new(&spaceship);
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        s.apply_environment(Environment::IonStorm);
+        assert_eq!(s.shield_layers.max, base.shield_layers.max / 2);
+        assert_eq!(s.shield_layers.current, s.shield_layers.max);
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        s.apply_environment(Environment::Nebula);
+        assert_eq!(
+            s.dodge_chance,
+            base.dodge_chance + NEBULA_DODGE_CHANCE_BONUS
+        );
+
+        // lasers and missiles damage, in the order of the modules
+        let damages = |s: &SpaceShipBattleCard| {
+            s.concrete_powerups
+                .iter()
+                .map(|p| p.effect.as_ref().unwrap().potential_damage())
+                .collect::<Vec<_>>()
+        };
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        s.apply_environment(Environment::AsteroidField);
+        assert_eq!(
+            damages(&s),
+            vec![
+                damages(&base)[0],
+                damages(&base)[1] - ASTEROID_FIELD_MISSILE_DAMAGE_MALUS
+            ]
+        );
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        s.apply_environment(Environment::SolarFlare);
+        assert_eq!(
+            damages(&s),
+            vec![
+                damages(&base)[0] + SOLAR_FLARE_LASER_DAMAGE_BONUS,
+                damages(&base)[1]
+            ]
+        );
+
+        // the environment is revealed when the match starts
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&spaceship);
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::Nebula, 1, 1);
+        let fight_log = fight_engine.take_fight_log();
+        assert!(matches!(
+            fight_log.entries[1].event,
+            BattleEvent::EnvironmentRevealed {
+                environment: Environment::Nebula
+            }
+        ));
+
+        // every environment can be rolled
+        let mut rng = RandomNumberGenerator::new(1);
+        let rolled = (0..100)
+            .map(|_| Environment::roll(&mut rng))
+            .collect::<Vec<_>>();
+        for environment in [
+            Environment::DeepSpace,
+            Environment::IonStorm,
+            Environment::Nebula,
+            Environment::AsteroidField,
+            Environment::SolarFlare,
+        ] {
+            assert!(rolled.contains(&environment));
+        }
+    }
+
+    #[test]
+    fn test_fight_with_mutations() {
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        for mutation in LT_MUTATIONS_UNCOMMON.into_iter().chain(LT_MUTATIONS_RARE) {
+            let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+            let spaceship = mock_spaceship(vec![], vec![], vec![mutation]);
+            let opponent_spaceship =
+                mock_spaceship(vec![pulse_laser_module.clone()], vec![], vec![]);
+            let fight_seed = 1;
+
+            let mut s = SpaceShipBattleCard::new(&spaceship);
+            let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+            // used to panic as soon as a mutation was part of the fight
+            let _ = fight_engine.fight(
+                &mut s,
+                &mut os,
+                Environment::DeepSpace,
+                fight_seed,
+                MATCH_MAX_TURN,
+            );
+        }
+    }
+
+    #[test]
+    fn test_mutation_ship_modifiers() {
+        let neural_overclock_mutation = LT_MUTATIONS_RARE
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Neural Overclock"))
+            .unwrap();
+        let kinetic_tendons_mutation = LT_MUTATIONS_RARE
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Kinetic Tendons"))
+            .unwrap();
+        let artillery_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("175mm Artillery"))
+            .unwrap();
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let artillery_charge_time = artillery_module.get_charge_time().unwrap();
+        let spaceship = mock_spaceship(
+            vec![artillery_module, pulse_laser_module],
+            vec![],
+            vec![neural_overclock_mutation, kinetic_tendons_mutation],
+        );
+
+        let s = SpaceShipBattleCard::new(&spaceship);
+
+        // all active powerups charge faster
+        assert_eq!(
+            s.concrete_powerups[0].charge_time,
+            artillery_charge_time - 2
+        );
+        // only projectile weapons hit harder
+        assert!(matches!(
+            s.concrete_powerups[0].effect,
+            Some(Effect::Fire { damage: 4, .. })
+        ));
+        assert!(matches!(
+            s.concrete_powerups[1].effect,
+            Some(Effect::Fire { damage: 1, .. })
+        ));
+        // mutations only bring passive bonuses, no effect
+        assert!(s.concrete_powerups[3].effect.is_none());
+    }
+
+    #[test]
+    fn test_fight_reactive_shield_booster() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let shield_booster_module = LT_MODULES_UNCOMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Emergency Shield Booster"))
+            .unwrap();
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![shield_booster_module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![pulse_laser_module], vec![], vec![]);
+        let fight_seed = 1;
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        let turns = 11;
+        let _ = fight_engine.fight(&mut s, &mut os, Environment::DeepSpace, fight_seed, turns);
+
+        // the layer taken down by the laser was restored right away
+        assert_eq!(s.shield_layers.current, s.shield_layers.max);
+        let fight_log = fight_engine.take_fight_log();
+        assert!(fight_log.entries.iter().any(|e| matches!(
+            e.event,
+            BattleEvent::PowerUpReacted {
+                powerup_index: 0,
+                trigger: Trigger::OnShieldLayerDown,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_reactive_powerups_respect_heat() {
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        fight_engine.set_fight_log_recording(true);
+        let burst_projector_module = LT_MODULES_RARE
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Burst Projector"))
+            .unwrap();
+        let spaceship = mock_spaceship(vec![burst_projector_module], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut rng = RandomNumberGenerator::new(1);
+
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let mut os = SpaceShipBattleCard::new(&opponent_spaceship);
+        s.pending_triggers = vec![TriggerEvent::Hit, TriggerEvent::Dodge, TriggerEvent::Hit];
+        fight_engine.dispatch_triggers(
+            std::slice::from_mut(&mut s),
+            std::slice::from_mut(&mut os),
+            &mut rng,
+        );
+
+        // only the first hit went through, the powerup is heating afterward
+        let reactions = fight_engine
+            .take_fight_log()
+            .entries
+            .iter()
+            .filter(|e| matches!(e.event, BattleEvent::PowerUpReacted { .. }))
+            .count();
+        assert_eq!(reactions, 1);
+        assert!(s.pending_triggers.is_empty());
+        assert!(!s.concrete_powerups[0].is_off_cooldown());
+    }
+
+    #[test]
+    fn test_reactive_hull_threshold() {
+        let spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let mut s = SpaceShipBattleCard::new(&spaceship);
+        let trigger = Trigger::OnHullBelowPercent(50);
+
+        assert!(!s.reacts_to(trigger, TriggerEvent::HullDamaged));
+        s.hull_hitpoints.deplete(s.hull_hitpoints.max / 2 + 1);
+        assert!(s.reacts_to(trigger, TriggerEvent::HullDamaged));
+        // only reacts to hull damage
+        assert!(!s.reacts_to(trigger, TriggerEvent::Hit));
     }
 }
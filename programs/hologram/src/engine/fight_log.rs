@@ -0,0 +1,100 @@
+use {
+    super::{BattleEvent, SpaceShipBattleCard},
+    crate::{error::HologramError, state::HitPoints},
+    anchor_lang::{prelude::*, solana_program::hash::Hasher},
+    std::io::Write,
+};
+
+// Record of a fight, enough to explain (and replay) it off-chain
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Default)]
+pub struct FightLog {
+    pub seed: u32,
+    // state of both sides before the first turn, one snapshot per spaceship
+    pub user: Vec<SpaceShipBattleCardSnapshot>,
+    pub opponent: Vec<SpaceShipBattleCardSnapshot>,
+    pub entries: Vec<FightLogEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct FightLogEntry {
+    pub turn: u16,
+    pub event: BattleEvent,
+}
+
+// Serializable subset of a SpaceShipBattleCard
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Default)]
+pub struct SpaceShipBattleCardSnapshot {
+    pub name: String,
+    pub id: u64,
+    pub hull_hitpoints: HitPoints,
+    pub armor_hitpoints: HitPoints,
+    pub shield_layers: HitPoints,
+    pub dodge_chance: u8,
+    pub jamming_nullifying_chance: u8,
+    pub critical_hit_chance: u8,
+    pub critical_hit_multiplier: u8,
+    pub initiative: u8,
+    pub capacitor: HitPoints,
+    // names of the powerups, in the same order as the battlecard concrete_powerups (events refer to them by index)
+    pub powerups: Vec<String>,
+}
+
+impl FightLog {
+    pub fn new(
+        user_fleet: &[SpaceShipBattleCard],
+        opponent_fleet: &[SpaceShipBattleCard],
+        seed: u32,
+    ) -> Self {
+        Self {
+            seed,
+            user: user_fleet.iter().map(|s| s.snapshot()).collect(),
+            opponent: opponent_fleet.iter().map(|s| s.snapshot()).collect(),
+            entries: vec![],
+        }
+    }
+
+    pub fn record(&mut self, turn: u16, event: BattleEvent) {
+        self.entries.push(FightLogEntry { turn, event });
+    }
+}
+
+// Running sha256 of the events of a fight, a cheap fingerprint to compare a replay with the on-chain match
+// Note: fed one event at a time as they are emitted, the fight log doesn't need to be kept around for it
+#[derive(Clone, Default)]
+pub struct EventStreamHasher {
+    hasher: Hasher,
+    failed: bool,
+}
+
+impl EventStreamHasher {
+    // hashes the same bytes as the serialized FightLogEntry
+    pub fn hash(&mut self, turn: u16, event: &BattleEvent) {
+        let mut writer = HasherWriter(&mut self.hasher);
+        if turn
+            .serialize(&mut writer)
+            .and_then(|_| event.serialize(&mut writer))
+            .is_err()
+        {
+            self.failed = true;
+        }
+    }
+
+    pub fn result(&self) -> Result<[u8; 32]> {
+        require!(!self.failed, HologramError::EventStreamHashFailed);
+        Ok(self.hasher.clone().result().to_bytes())
+    }
+}
+
+// Serialize straight into the hasher, no intermediate buffer
+struct HasherWriter<'a>(&'a mut Hasher);
+
+impl Write for HasherWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.hash(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -1,16 +1,21 @@
 use {
     crate::{
+        engine::Effect,
         error::HologramError,
         state::{
-            Bonuses, Drone, DroneClass, DroneSize, JammerModuleStats, Module, ModuleClass,
-            Mutation, Passive,
+            AfterburnerStats, Bonuses, CapacitorModuleStats, Drone, DroneClass, DroneSize,
+            JammerModuleStats, LootEntry, LootItem, LootKind, LootTable, MetabolicEffect,
+            MetabolicStats, Module, ModuleClass, Mutation, MutationClass, MutationFamily, Passive,
+            PointDefenseStats,
             Rarity::{self, *},
-            RepairModuleStats, RepairTarget, Shots, WeaponModuleStats, WeaponType,
+            ReactiveModuleStats, RepairModuleStats, RepairTarget, ShipModifier, Shots, StatusKind,
+            StatusModuleStats, StatusTarget, Targeting, Tier, Trigger, WeaponModuleStats,
+            WeaponType,
         },
         utils::{LimitedString, RandomNumberGenerator},
         BASE_HEAT,
     },
-    anchor_lang::{require, Result},
+    anchor_lang::{err, require, Result},
 };
 
 // Totalling 100
@@ -18,73 +23,138 @@ pub const COMMON_RARITY_CHANCE: u8 = 55;
 pub const UNCOMMON_RARITY_CHANCE: u8 = 25;
 pub const RARE_RARITY_CHANCE: u8 = 15;
 pub const FACTION_RARITY_CHANCE: u8 = 5;
+// Mutations are either Uncommon or Rare
+pub const RARE_MUTATION_CHANCE: u8 = 25;
 
 pub struct LootEngine {}
 
+pub enum MutationDrop {
+    // a mutation the spaceship doesn't have
+    New(Mutation),
+    // replaces the owned mutation of the same family
+    Evolution(Mutation),
+    // the spaceship owns every mutation it could roll
+    Exhausted,
+}
+
 impl LootEngine {
     pub fn drop_module(
         rng: &mut RandomNumberGenerator,
+        loot_table: &LootTable,
         faction_rarity_enabled: bool,
+        pity_bonus: u8,
     ) -> Result<Module> {
-        let drop_rarity = Self::get_drop_rarity(rng, faction_rarity_enabled);
-
-        let loot_table = match drop_rarity {
-            Common => LT_MODULES_COMMON.to_vec(),
-            Uncommon => LT_MODULES_UNCOMMON.to_vec(),
-            Rare => LT_MODULES_RARE.to_vec(),
-            Faction => LT_MODULES_FACTION.to_vec(),
-        };
-        require!(!loot_table.is_empty(), HologramError::InvalidLootTable);
+        let drop_rarity = Self::get_drop_rarity(rng, faction_rarity_enabled, pity_bonus);
 
-        let roll = rng.roll_dice(loot_table.len()) as usize;
-        Ok(loot_table[roll - 1].clone())
+        let bucket = loot_table.bucket(LootKind::Module, drop_rarity);
+        match &Self::roll_entry(rng, &bucket)?.item {
+            LootItem::Module(module) => Ok(module.clone()),
+            _ => err!(HologramError::InvalidLootTable),
+        }
     }
 
     pub fn drop_drone(
         rng: &mut RandomNumberGenerator,
+        loot_table: &LootTable,
         faction_rarity_enabled: bool,
+        pity_bonus: u8,
     ) -> Result<Drone> {
-        let drop_rarity = Self::get_drop_rarity(rng, faction_rarity_enabled);
-
-        let loot_table = match drop_rarity {
-            Common => LT_DRONE_COMMON.to_vec(),
-            Uncommon => LT_DRONE_OFFENSIVE_UNCOMMON.to_vec(),
-            Rare => LT_DRONE_OFFENSIVE_RARE.to_vec(),
-            Faction => LT_DRONE_OFFENSIVE_FACTION.to_vec(),
-        };
+        let drop_rarity = Self::get_drop_rarity(rng, faction_rarity_enabled, pity_bonus);
 
-        require!(!loot_table.is_empty(), HologramError::InvalidLootTable);
-        let roll = rng.roll_dice(loot_table.len()) as usize;
-        Ok(loot_table[roll - 1].clone())
+        let bucket = loot_table.bucket(LootKind::Drone, drop_rarity);
+        match &Self::roll_entry(rng, &bucket)?.item {
+            LootItem::Drone(drone) => Ok(drone.clone()),
+            _ => err!(HologramError::InvalidLootTable),
+        }
     }
 
+    // The spaceship never rolls a mutation it owns, nor one of an owned family that isn't rarer
+    // A rarer mutation of an owned family evolves it, see SpaceShip::evolve_mutation
     pub fn drop_mutation(
         rng: &mut RandomNumberGenerator,
-        _owned_mutation: &[Mutation],
-    ) -> Result<Mutation> {
-        let _roll = rng.roll_dice(100);
+        loot_table: &LootTable,
+        owned_mutations: &[&Mutation],
+        pity_bonus: u8,
+    ) -> Result<MutationDrop> {
+        let roll = rng.roll_dice(100);
+        let (drop_rarity, other_rarity) =
+            match roll <= RARE_MUTATION_CHANCE as u64 + pity_bonus as u64 {
+                true => (Rare, Uncommon),
+                false => (Uncommon, Rare),
+            };
 
-        // @TODO: Here will require a system where we can't drop the same mutation twice
+        // when the spaceship already owns the whole rolled rarity, fall back on the other one
+        for rarity in [drop_rarity, other_rarity] {
+            let bucket = loot_table
+                .bucket(LootKind::Mutation, rarity)
+                .into_iter()
+                .filter(|e| match &e.item {
+                    LootItem::Mutation(mutation) => {
+                        Self::can_drop_mutation(mutation, owned_mutations)
+                    }
+                    _ => false,
+                })
+                .collect::<Vec<_>>();
+            if bucket.is_empty() {
+                continue;
+            }
+            return match &Self::roll_entry(rng, &bucket)?.item {
+                LootItem::Mutation(mutation) => {
+                    let evolves_owned = owned_mutations
+                        .iter()
+                        .any(|m| m.family.is_some() && m.family == mutation.family);
+                    Ok(match evolves_owned {
+                        true => MutationDrop::Evolution(mutation.clone()),
+                        false => MutationDrop::New(mutation.clone()),
+                    })
+                }
+                _ => err!(HologramError::InvalidLootTable),
+            };
+        }
+        Ok(MutationDrop::Exhausted)
+    }
 
-        Ok(Mutation {
-            name: LimitedString::new("Fungal Growth"),
-            rarity: Common,
-            is_active: false,
+    fn can_drop_mutation(mutation: &Mutation, owned_mutations: &[&Mutation]) -> bool {
+        !owned_mutations.iter().any(|owned| {
+            owned.name == mutation.name
+                || (owned.family.is_some()
+                    && owned.family == mutation.family
+                    && owned.rarity >= mutation.rarity)
         })
     }
 
+    // Pick one of the entries, proportionally to their weight
+    fn roll_entry<'a>(
+        rng: &mut RandomNumberGenerator,
+        bucket: &[&'a LootEntry],
+    ) -> Result<&'a LootEntry> {
+        let total_weight: u64 = bucket.iter().map(|e| e.weight as u64).sum();
+        require!(total_weight > 0, HologramError::InvalidLootTable);
+
+        let mut roll = rng.roll_dice(total_weight as usize);
+        for entry in bucket.iter() {
+            if roll <= entry.weight as u64 {
+                return Ok(entry);
+            }
+            roll -= entry.weight as u64;
+        }
+        err!(HologramError::InvalidLootTable)
+    }
+
+    // Note: the pity bonus is added to the Rare and Faction chances, see CrateOdds::pity_bonus
     pub fn get_drop_rarity(
         rng: &mut RandomNumberGenerator,
         faction_rarity_enabled: bool,
+        pity_bonus: u8,
     ) -> Rarity {
         let mut cumulative_chance = 0;
         let rarity_chances = [
-            (COMMON_RARITY_CHANCE, Common),
-            (UNCOMMON_RARITY_CHANCE, Uncommon),
-            (RARE_RARITY_CHANCE, Rare),
+            (COMMON_RARITY_CHANCE as u16, Common),
+            (UNCOMMON_RARITY_CHANCE as u16, Uncommon),
+            (RARE_RARITY_CHANCE as u16 + pity_bonus as u16, Rare),
             (
                 if faction_rarity_enabled {
-                    FACTION_RARITY_CHANCE
+                    FACTION_RARITY_CHANCE as u16 + pity_bonus as u16
                 } else {
                     0
                 },
@@ -92,8 +162,8 @@ impl LootEngine {
             ),
         ];
 
-        let total_chance: u8 = rarity_chances.iter().map(|(chance, _)| *chance).sum();
-        let roll = rng.roll_dice(total_chance as usize) as u8;
+        let total_chance: u16 = rarity_chances.iter().map(|(chance, _)| *chance).sum();
+        let roll = rng.roll_dice(total_chance as usize) as u16;
 
         for (chance, rarity) in rarity_chances.iter() {
             cumulative_chance += chance;
@@ -114,10 +184,12 @@ pub const LT_STARTER_OFFENSIVE_MODULES: [Module; 2] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Projectile,
             damage: 1,
+            max_damage: None,
             charge_time: 11,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("Civilian Mining Laser"),
@@ -125,15 +197,17 @@ pub const LT_STARTER_OFFENSIVE_MODULES: [Module; 2] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 1,
+            max_damage: None,
             charge_time: 11,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
 ];
 
 // ------------------ COMMON ---------------------------------------------------------------------
-pub const LT_MODULES_COMMON: [Module; 5] = [
+pub const LT_MODULES_COMMON: [Module; 12] = [
     // Offensive ----------------------------------------------------------------------------------
     Module {
         name: LimitedString::new_const("Pulse Laser"),
@@ -141,10 +215,12 @@ pub const LT_MODULES_COMMON: [Module; 5] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 1,
+            max_damage: None,
             charge_time: 10,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("Dual Pulse Laser"),
@@ -152,10 +228,12 @@ pub const LT_MODULES_COMMON: [Module; 5] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 1,
+            max_damage: None,
             charge_time: 21,
             shots: Shots::Salvo(2),
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("Slicer"),
@@ -163,10 +241,12 @@ pub const LT_MODULES_COMMON: [Module; 5] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Plasma,
             damage: 2,
+            max_damage: None,
             charge_time: 18,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("175mm Artillery"),
@@ -174,10 +254,12 @@ pub const LT_MODULES_COMMON: [Module; 5] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Projectile,
             damage: 3,
+            max_damage: None,
             charge_time: 28,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("Light Missile Launcher I"),
@@ -185,15 +267,164 @@ pub const LT_MODULES_COMMON: [Module; 5] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Missile,
             damage: 2,
+            max_damage: None,
             charge_time: 19,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
+    },
+    // Anti-drone -------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Flak Cannon"),
+        rarity: Common,
+        class: ModuleClass::AntiDrone(WeaponModuleStats {
+            weapon_type: WeaponType::Projectile,
+            damage: 1,
+            max_damage: None,
+            charge_time: 12,
+            shots: Shots::Salvo(2),
+        }),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Other ----------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Small Armor Repairer I"),
+        rarity: Common,
+        class: ModuleClass::Repairer(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 4,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            RepairModuleStats {
+                repair_amount: 2,
+                charge_time: 14,
+                target: RepairTarget::Armor,
+            },
+        ),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Disruption -------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Target Painter"),
+        rarity: Common,
+        class: ModuleClass::Disruptor(StatusModuleStats {
+            status: StatusKind::DodgePenalty { amount: 10 },
+            target: StatusTarget::Target,
+            duration: 6,
+            charge_time: 12,
+        }),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Point defense -----------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Point Defense Turret"),
+        rarity: Common,
+        class: ModuleClass::PointDefense(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            PointDefenseStats {
+                intercept_chance: 30,
+                heat: 4,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Reactive ---------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Riposte Autocannon"),
+        rarity: Common,
+        class: ModuleClass::Reactive(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            ReactiveModuleStats {
+                trigger: Trigger::OnDodge,
+                effect: Effect::Fire {
+                    damage: 1,
+                    max_damage: None,
+                    shots: Shots::Single,
+                    weapon_type: WeaponType::Projectile,
+                    targeting: Targeting::Ship,
+                },
+                heat: 3,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Capacitor --------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Capacitor Battery"),
+        rarity: Common,
+        class: ModuleClass::Capacitor(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 1,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            CapacitorModuleStats {
+                capacity: 10,
+                recharge_per_turn: 0,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Propulsion ---------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("1MN Afterburner"),
+        rarity: Common,
+        class: ModuleClass::Afterburner(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 2,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 5,
+            },
+            AfterburnerStats { hull_penalty: 3 },
+        ),
+        is_active: false,
+        tier: Tier::I,
     },
 ];
 
 // ------------------ UNCOMMON ---------------------------------------------------------------------
-pub const LT_MODULES_UNCOMMON: [Module; 4] = [
+pub const LT_MODULES_UNCOMMON: [Module; 14] = [
     // Offensive ----------------------------------------------------------------------------------
     Module {
         name: LimitedString::new_const("Heavy Pulse Laser"),
@@ -201,10 +432,12 @@ pub const LT_MODULES_UNCOMMON: [Module; 4] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 2,
+            max_damage: None,
             charge_time: 16,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("125mm Dual Autocannon"),
@@ -212,10 +445,12 @@ pub const LT_MODULES_UNCOMMON: [Module; 4] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Projectile,
             damage: 1,
+            max_damage: None,
             charge_time: 18,
             shots: Shots::Salvo(2),
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("Assault Missile Launcher"),
@@ -223,10 +458,26 @@ pub const LT_MODULES_UNCOMMON: [Module; 4] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Missile,
             damage: 4,
+            max_damage: None,
             charge_time: 30,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
+    },
+    // Anti-drone -------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Flak Battery"),
+        rarity: Uncommon,
+        class: ModuleClass::AntiDrone(WeaponModuleStats {
+            weapon_type: WeaponType::Projectile,
+            damage: 2,
+            max_damage: None,
+            charge_time: 14,
+            shots: Shots::Salvo(2),
+        }),
+        is_active: true,
+        tier: Tier::I,
     },
     // Other ----------------------------------------------------------------------------------
     Module {
@@ -235,9 +486,13 @@ pub const LT_MODULES_UNCOMMON: [Module; 4] = [
         class: ModuleClass::Capacitative(
             Bonuses {
                 hull_hitpoints: 0,
+                armor_hitpoints: 0,
                 shield_layers: 1,
                 dodge_chance: 0,
                 jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
             },
             Passive::CapacitativeRepair {
                 recent_damage_threshold: 8,
@@ -247,11 +502,197 @@ pub const LT_MODULES_UNCOMMON: [Module; 4] = [
             },
         ),
         is_active: false,
+        tier: Tier::I,
+    },
+    Module {
+        name: LimitedString::new_const("Medium Armor Repairer I"),
+        rarity: Uncommon,
+        class: ModuleClass::Repairer(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 8,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            RepairModuleStats {
+                repair_amount: 3,
+                charge_time: 16,
+                target: RepairTarget::Armor,
+            },
+        ),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Disruption -------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("EM Smartbomb"),
+        rarity: Uncommon,
+        class: ModuleClass::Disruptor(StatusModuleStats {
+            status: StatusKind::DronesDisabled,
+            target: StatusTarget::Target,
+            duration: 4,
+            charge_time: 20,
+        }),
+        is_active: true,
+        tier: Tier::I,
+    },
+    Module {
+        name: LimitedString::new_const("Incendiary Rocket Pod"),
+        rarity: Uncommon,
+        class: ModuleClass::Disruptor(StatusModuleStats {
+            status: StatusKind::Burn { damage: 1 },
+            target: StatusTarget::Target,
+            duration: 3,
+            charge_time: 20,
+        }),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Reactive ---------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Emergency Shield Booster"),
+        rarity: Uncommon,
+        class: ModuleClass::Reactive(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 1,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            ReactiveModuleStats {
+                trigger: Trigger::OnShieldLayerDown,
+                effect: Effect::Repair {
+                    target: RepairTarget::Shield,
+                    amount: 1,
+                },
+                heat: 12,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    Module {
+        name: LimitedString::new_const("Damage Control Unit"),
+        rarity: Uncommon,
+        class: ModuleClass::Reactive(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            ReactiveModuleStats {
+                trigger: Trigger::OnHullBelowPercent(30),
+                effect: Effect::Repair {
+                    target: RepairTarget::Hull,
+                    amount: 3,
+                },
+                heat: 15,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Capacitor --------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Capacitor Power Relay"),
+        rarity: Uncommon,
+        class: ModuleClass::Capacitor(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            CapacitorModuleStats {
+                capacity: 0,
+                recharge_per_turn: 1,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Disruption -------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Energy Neutralizer"),
+        rarity: Uncommon,
+        class: ModuleClass::Jammer(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            JammerModuleStats {
+                charge_burn: 0,
+                charge_time: 12,
+                capacitor_drain: 8,
+            },
+        ),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Upgrade ----------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Targeting Computer"),
+        rarity: Uncommon,
+        class: ModuleClass::Upgrade(Bonuses {
+            hull_hitpoints: 0,
+            armor_hitpoints: 0,
+            shield_layers: 0,
+            dodge_chance: 0,
+            jamming_nullifying_chance: 0,
+            critical_hit_chance: 10,
+            critical_hit_damage: 0,
+            initiative: 0,
+        }),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Propulsion ---------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("10MN Afterburner"),
+        rarity: Uncommon,
+        class: ModuleClass::Afterburner(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 3,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 10,
+            },
+            AfterburnerStats { hull_penalty: 5 },
+        ),
+        is_active: false,
+        tier: Tier::I,
     },
 ];
 
 // ------------------ RARE ---------------------------------------------------------------------
-pub const LT_MODULES_RARE: [Module; 6] = [
+pub const LT_MODULES_RARE: [Module; 12] = [
     // Offensive ----------------------------------------------------------------------------------
     Module {
         name: LimitedString::new_const("280mm 'Howitzer' Artillery"),
@@ -259,10 +700,12 @@ pub const LT_MODULES_RARE: [Module; 6] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Projectile,
             damage: 5,
+            max_damage: None,
             charge_time: 30,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("'Halberd' Slicer"),
@@ -270,10 +713,12 @@ pub const LT_MODULES_RARE: [Module; 6] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 4,
+            max_damage: None,
             charge_time: 23,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("Rapid Light Missile Launcher"),
@@ -281,10 +726,12 @@ pub const LT_MODULES_RARE: [Module; 6] = [
         class: ModuleClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Missile,
             damage: 2,
+            max_damage: None,
             charge_time: 12,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     // Other ----------------------------------------------------------------------------------
     Module {
@@ -293,9 +740,13 @@ pub const LT_MODULES_RARE: [Module; 6] = [
         class: ModuleClass::Repairer(
             Bonuses {
                 hull_hitpoints: 0,
+                armor_hitpoints: 0,
                 shield_layers: 1,
                 dodge_chance: 0,
                 jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
             },
             RepairModuleStats {
                 repair_amount: 1,
@@ -304,6 +755,7 @@ pub const LT_MODULES_RARE: [Module; 6] = [
             },
         ),
         is_active: true,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("Capacitative Armor"),
@@ -311,9 +763,13 @@ pub const LT_MODULES_RARE: [Module; 6] = [
         class: ModuleClass::Capacitative(
             Bonuses {
                 hull_hitpoints: 5,
+                armor_hitpoints: 0,
                 shield_layers: 0,
                 dodge_chance: 0,
                 jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
             },
             Passive::CapacitativeRepair {
                 recent_damage_threshold: 5,
@@ -323,6 +779,7 @@ pub const LT_MODULES_RARE: [Module; 6] = [
             },
         ),
         is_active: false,
+        tier: Tier::I,
     },
     Module {
         name: LimitedString::new_const("'Phantom' Burst Jammer"),
@@ -330,16 +787,136 @@ pub const LT_MODULES_RARE: [Module; 6] = [
         class: ModuleClass::Jammer(
             Bonuses {
                 hull_hitpoints: 0,
+                armor_hitpoints: 0,
                 shield_layers: 0,
                 dodge_chance: 0,
                 jamming_nullifying_chance: 10,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
             },
             JammerModuleStats {
                 charge_burn: 5,
                 charge_time: 15,
+                capacitor_drain: 4,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Disruption -------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Stasis Webifier"),
+        rarity: Rare,
+        class: ModuleClass::Disruptor(StatusModuleStats {
+            status: StatusKind::ChargeRate { modifier: -1 },
+            target: StatusTarget::Target,
+            duration: 3,
+            charge_time: 18,
+        }),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Reactive ---------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Burst Projector"),
+        rarity: Rare,
+        class: ModuleClass::Reactive(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 5,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            ReactiveModuleStats {
+                trigger: Trigger::OnHit,
+                effect: Effect::Jam {
+                    charge_burn: 3,
+                    capacitor_drain: 0,
+                },
+                heat: 8,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Point defense -----------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Aegis Point Defense Array"),
+        rarity: Rare,
+        class: ModuleClass::PointDefense(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 3,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            PointDefenseStats {
+                intercept_chance: 50,
+                heat: 2,
+            },
+        ),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Offensive ----------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("'Scattershot' Autocannon"),
+        rarity: Rare,
+        class: ModuleClass::Weapon(WeaponModuleStats {
+            weapon_type: WeaponType::Projectile,
+            damage: 1,
+            max_damage: Some(4),
+            charge_time: 20,
+            shots: Shots::Salvo(2),
+        }),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Upgrade ----------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("Fire Control Array"),
+        rarity: Rare,
+        class: ModuleClass::Upgrade(Bonuses {
+            hull_hitpoints: 0,
+            armor_hitpoints: 0,
+            shield_layers: 0,
+            dodge_chance: 0,
+            jamming_nullifying_chance: 0,
+            critical_hit_chance: 10,
+            critical_hit_damage: 50,
+            initiative: 0,
+        }),
+        is_active: false,
+        tier: Tier::I,
+    },
+    // Propulsion ---------------------------------------------------------------------------------
+    Module {
+        name: LimitedString::new_const("'Quasar' Microwarpdrive"),
+        rarity: Rare,
+        class: ModuleClass::Afterburner(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 20,
             },
+            AfterburnerStats { hull_penalty: 8 },
         ),
         is_active: false,
+        tier: Tier::I,
     },
 ];
 
@@ -351,10 +928,12 @@ pub const LT_MODULES_FACTION: [Module; 1] = [Module {
     class: ModuleClass::Weapon(WeaponModuleStats {
         weapon_type: WeaponType::Laser,
         damage: 1,
+        max_damage: None,
         charge_time: 16,
         shots: Shots::Salvo(3),
     }),
     is_active: true,
+    tier: Tier::I,
 }];
 
 // ------------------ DRONES ---------------------------------------------------------------------
@@ -368,10 +947,12 @@ pub const LT_DRONE_COMMON: [Drone; 2] = [
         class: DroneClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Projectile,
             damage: 1,
+            max_damage: None,
             charge_time: 10,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Drone {
         name: LimitedString::new_const("Acolyte"),
@@ -380,14 +961,16 @@ pub const LT_DRONE_COMMON: [Drone; 2] = [
         class: DroneClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 1,
+            max_damage: None,
             charge_time: 20,
             shots: Shots::Salvo(2),
         }),
         is_active: true,
+        tier: Tier::I,
     },
 ];
 
-pub const LT_DRONE_OFFENSIVE_UNCOMMON: [Drone; 2] = [
+pub const LT_DRONE_OFFENSIVE_UNCOMMON: [Drone; 4] = [
     Drone {
         name: LimitedString::new_const("Augmented Hornet"),
         rarity: Uncommon,
@@ -395,10 +978,12 @@ pub const LT_DRONE_OFFENSIVE_UNCOMMON: [Drone; 2] = [
         class: DroneClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Projectile,
             damage: 1,
+            max_damage: None,
             charge_time: 9,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Drone {
         name: LimitedString::new_const("Augmented Acolyte"),
@@ -407,10 +992,39 @@ pub const LT_DRONE_OFFENSIVE_UNCOMMON: [Drone; 2] = [
         class: DroneClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 1,
+            max_damage: None,
             charge_time: 17,
             shots: Shots::Salvo(2),
         }),
         is_active: true,
+        tier: Tier::I,
+    },
+    // Interceptor ------------------------------------------------------------------------------
+    Drone {
+        name: LimitedString::new_const("Warden"),
+        rarity: Uncommon,
+        size: DroneSize::Light,
+        class: DroneClass::Interceptor(WeaponModuleStats {
+            weapon_type: WeaponType::Laser,
+            damage: 1,
+            max_damage: None,
+            charge_time: 8,
+            shots: Shots::Single,
+        }),
+        is_active: true,
+        tier: Tier::I,
+    },
+    // Point defense -----------------------------------------------------------------------------
+    Drone {
+        name: LimitedString::new_const("Sentinel"),
+        rarity: Uncommon,
+        size: DroneSize::Light,
+        class: DroneClass::PointDefense(PointDefenseStats {
+            intercept_chance: 30,
+            heat: 3,
+        }),
+        is_active: false,
+        tier: Tier::I,
     },
 ];
 
@@ -422,10 +1036,12 @@ pub const LT_DRONE_OFFENSIVE_RARE: [Drone; 2] = [
         class: DroneClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Projectile,
             damage: 2,
+            max_damage: None,
             charge_time: 13,
             shots: Shots::Single,
         }),
         is_active: true,
+        tier: Tier::I,
     },
     Drone {
         name: LimitedString::new_const("Infiltrator"),
@@ -434,10 +1050,12 @@ pub const LT_DRONE_OFFENSIVE_RARE: [Drone; 2] = [
         class: DroneClass::Weapon(WeaponModuleStats {
             weapon_type: WeaponType::Laser,
             damage: 1,
+            max_damage: None,
             charge_time: 18,
             shots: Shots::Salvo(3),
         }),
         is_active: true,
+        tier: Tier::I,
     },
 ];
 
@@ -448,20 +1066,352 @@ pub const LT_DRONE_OFFENSIVE_FACTION: [Drone; 1] = [Drone {
     class: DroneClass::Weapon(WeaponModuleStats {
         weapon_type: WeaponType::Missile,
         damage: 2,
+        max_damage: None,
         charge_time: 30,
         shots: Shots::Salvo(3),
     }),
     is_active: true,
+    tier: Tier::I,
 }];
 
 // ------------------ MUTATIONS ------------------
-pub const LT_MUTATIONS_UNCOMMON: [Mutation; 1] = [Mutation {
-    name: LimitedString::new_const("Nanite Coating"),
-    rarity: Uncommon,
-    is_active: false,
-}];
-pub const LT_MUTATIONS_RARE: [Mutation; 1] = [Mutation {
-    name: LimitedString::new_const("Nanite Outbreak"),
-    rarity: Uncommon,
-    is_active: false,
-}];
+pub const LT_MUTATIONS_UNCOMMON: [Mutation; 4] = [
+    Mutation {
+        name: LimitedString::new_const("Nanite Coating"),
+        rarity: Uncommon,
+        class: MutationClass::Growth(Bonuses {
+            hull_hitpoints: 0,
+            armor_hitpoints: 4,
+            shield_layers: 0,
+            dodge_chance: 0,
+            jamming_nullifying_chance: 0,
+            critical_hit_chance: 0,
+            critical_hit_damage: 0,
+            initiative: 0,
+        }),
+        is_active: false,
+        family: Some(MutationFamily::Nanite),
+    },
+    Mutation {
+        name: LimitedString::new_const("Chitinous Carapace"),
+        rarity: Uncommon,
+        class: MutationClass::Growth(Bonuses {
+            hull_hitpoints: 3,
+            armor_hitpoints: 0,
+            shield_layers: 0,
+            dodge_chance: 0,
+            jamming_nullifying_chance: 0,
+            critical_hit_chance: 0,
+            critical_hit_damage: 0,
+            initiative: 0,
+        }),
+        is_active: false,
+        family: None,
+    },
+    Mutation {
+        name: LimitedString::new_const("Fungal Growth"),
+        rarity: Uncommon,
+        class: MutationClass::Metabolic(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 3,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            MetabolicStats {
+                effect: MetabolicEffect::Repair {
+                    target: RepairTarget::Armor,
+                    amount: 1,
+                },
+                heat: 6,
+            },
+        ),
+        is_active: false,
+        family: None,
+    },
+    Mutation {
+        name: LimitedString::new_const("Regenerative Tissue"),
+        rarity: Uncommon,
+        class: MutationClass::Metabolic(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            MetabolicStats {
+                effect: MetabolicEffect::Repair {
+                    target: RepairTarget::Hull,
+                    amount: 1,
+                },
+                heat: 10,
+            },
+        ),
+        is_active: false,
+        family: Some(MutationFamily::Regeneration),
+    },
+];
+pub const LT_MUTATIONS_RARE: [Mutation; 4] = [
+    Mutation {
+        name: LimitedString::new_const("Nanite Outbreak"),
+        rarity: Rare,
+        class: MutationClass::Metabolic(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            MetabolicStats {
+                effect: MetabolicEffect::Status {
+                    status: StatusKind::Burn { damage: 1 },
+                    target: StatusTarget::Target,
+                    duration: 2,
+                },
+                heat: 12,
+            },
+        ),
+        is_active: false,
+        family: Some(MutationFamily::Nanite),
+    },
+    Mutation {
+        name: LimitedString::new_const("Neural Overclock"),
+        rarity: Rare,
+        class: MutationClass::Adaptive(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            ShipModifier::ChargeTimeReduction(2),
+        ),
+        is_active: false,
+        family: None,
+    },
+    Mutation {
+        name: LimitedString::new_const("Kinetic Tendons"),
+        rarity: Rare,
+        class: MutationClass::Adaptive(
+            Bonuses {
+                hull_hitpoints: 0,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 5,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            ShipModifier::WeaponDamage(WeaponType::Projectile, 1),
+        ),
+        is_active: false,
+        family: None,
+    },
+    Mutation {
+        name: LimitedString::new_const("Hyperplastic Regrowth"),
+        rarity: Rare,
+        class: MutationClass::Metabolic(
+            Bonuses {
+                hull_hitpoints: 2,
+                armor_hitpoints: 0,
+                shield_layers: 0,
+                dodge_chance: 0,
+                jamming_nullifying_chance: 0,
+                critical_hit_chance: 0,
+                critical_hit_damage: 0,
+                initiative: 0,
+            },
+            MetabolicStats {
+                effect: MetabolicEffect::Repair {
+                    target: RepairTarget::Hull,
+                    amount: 2,
+                },
+                heat: 10,
+            },
+        ),
+        is_active: false,
+        family: Some(MutationFamily::Regeneration),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::state::{mock_spaceship, CargoItem},
+    };
+
+    fn family_mutation(mutations: &[Mutation], family: MutationFamily) -> Mutation {
+        mutations
+            .iter()
+            .find(|m| m.family == Some(family))
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_drop_module_weighted() {
+        let mut loot_table = LootTable::default();
+        let common_modules = loot_table
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.item.kind() == LootKind::Module && e.item.rarity() == Common)
+            .map(|(i, _)| i as u8)
+            .collect::<Vec<_>>();
+        let favorite = common_modules[0];
+        loot_table.set_entry_weight(favorite, u8::MAX).unwrap();
+        for i in common_modules.iter().skip(1) {
+            loot_table.set_entry_weight(*i, 1).unwrap();
+        }
+
+        // drops are weighted within their rarity
+        let favorite_name = loot_table.entries[favorite as usize].item.name();
+        let mut rng = RandomNumberGenerator::new(1);
+        let drops = (0..100)
+            .map(|_| LootEngine::drop_module(&mut rng, &loot_table, false, 0).unwrap())
+            .filter(|m| m.rarity == Common)
+            .collect::<Vec<_>>();
+        let favorite_drops = drops.iter().filter(|m| m.name == favorite_name).count();
+        assert!(favorite_drops * 2 > drops.len());
+    }
+
+    #[test]
+    fn test_drop_rarity_pity_bonus() {
+        let rarities = |faction_rarity_enabled: bool, pity_bonus: u8| {
+            let mut rng = RandomNumberGenerator::new(1);
+            (0..1000)
+                .map(|_| LootEngine::get_drop_rarity(&mut rng, faction_rarity_enabled, pity_bonus))
+                .collect::<Vec<_>>()
+        };
+        let count =
+            |rarities: &[Rarity], rarity: Rarity| rarities.iter().filter(|r| **r == rarity).count();
+
+        // past the threshold, Rares drop more often
+        assert!(count(&rarities(false, 40), Rare) > count(&rarities(false, 0), Rare) * 2);
+        assert_eq!(count(&rarities(false, u8::MAX), Faction), 0);
+        assert!(count(&rarities(true, 40), Faction) > count(&rarities(true, 0), Faction));
+    }
+
+    #[test]
+    fn test_drop_mutation_new() {
+        let loot_table = LootTable::default();
+        let mut rng = RandomNumberGenerator::new(1);
+        for _ in 0..100 {
+            assert!(matches!(
+                LootEngine::drop_mutation(&mut rng, &loot_table, &[], 0).unwrap(),
+                MutationDrop::New(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_drop_mutation_evolution() {
+        let loot_table = LootTable::default();
+        let mut rng = RandomNumberGenerator::new(1);
+        let nanite_coating = family_mutation(&LT_MUTATIONS_UNCOMMON, MutationFamily::Nanite);
+        let mut evolutions = 0;
+        for _ in 0..100 {
+            match LootEngine::drop_mutation(&mut rng, &loot_table, &[&nanite_coating], 0).unwrap() {
+                MutationDrop::Evolution(mutation) => {
+                    evolutions += 1;
+                    assert_eq!(mutation.family, Some(MutationFamily::Nanite));
+                    assert_eq!(mutation.rarity, Rare);
+                }
+                MutationDrop::New(mutation) => {
+                    assert_ne!(mutation.family, Some(MutationFamily::Nanite))
+                }
+                MutationDrop::Exhausted => unreachable!(),
+            }
+        }
+        assert!(evolutions > 0);
+    }
+
+    #[test]
+    fn test_drop_mutation_exhausted() {
+        let loot_table = LootTable::default();
+        let mut rng = RandomNumberGenerator::new(1);
+        // owning the Rare of a family covers its Uncommon
+        let owned_mutations = LT_MUTATIONS_RARE
+            .iter()
+            .chain(LT_MUTATIONS_UNCOMMON.iter().filter(|m| m.family.is_none()))
+            .collect::<Vec<_>>();
+        for pity_bonus in [0, u8::MAX] {
+            assert!(matches!(
+                LootEngine::drop_mutation(&mut rng, &loot_table, &owned_mutations, pity_bonus)
+                    .unwrap(),
+                MutationDrop::Exhausted
+            ));
+        }
+    }
+
+    #[test]
+    fn test_drop_mutation_no_duplicate() {
+        let loot_table = LootTable::default();
+        let mut rng = RandomNumberGenerator::new(1);
+        let nanite_coating = family_mutation(&LT_MUTATIONS_UNCOMMON, MutationFamily::Nanite);
+
+        // every mutation is rolled once, the family ones either as new or evolving the owned one
+        let mut spaceship = mock_spaceship(vec![], vec![], vec![]);
+        spaceship
+            .cargo_hold
+            .push(CargoItem::Mutation(nanite_coating.clone()));
+        let mut evolutions = 0;
+        loop {
+            match LootEngine::drop_mutation(&mut rng, &loot_table, &spaceship.owned_mutations(), 0)
+                .unwrap()
+            {
+                MutationDrop::New(mutation) => {
+                    assert!(!spaceship
+                        .owned_mutations()
+                        .iter()
+                        .any(|m| m.name == mutation.name
+                            || (m.family.is_some() && m.family == mutation.family)));
+                    spaceship.mutations.push(mutation);
+                }
+                MutationDrop::Evolution(mutation) => {
+                    evolutions += 1;
+                    spaceship.evolve_mutation(mutation).unwrap();
+                }
+                MutationDrop::Exhausted => break,
+            }
+        }
+        // the Uncommon of each family got replaced by its Rare
+        let owned_mutations = spaceship.owned_mutations();
+        assert_eq!(evolutions, 2);
+        assert_eq!(
+            owned_mutations.len(),
+            LT_MUTATIONS_UNCOMMON.len() + LT_MUTATIONS_RARE.len() - 2
+        );
+        assert!(!owned_mutations
+            .iter()
+            .any(|m| m.name == nanite_coating.name));
+
+        // an Uncommon of an owned family can't be rolled over the Rare one
+        let nanite_outbreak = family_mutation(&LT_MUTATIONS_RARE, MutationFamily::Nanite);
+        for _ in 0..100 {
+            if let MutationDrop::New(mutation) | MutationDrop::Evolution(mutation) =
+                LootEngine::drop_mutation(&mut rng, &loot_table, &[&nanite_outbreak], 0).unwrap()
+            {
+                assert_ne!(mutation.family, Some(MutationFamily::Nanite));
+            }
+        }
+    }
+}
@@ -1,10 +1,13 @@
 pub mod battlecard;
 pub mod concrete_powerup;
 pub mod effect;
+pub mod environment;
 pub mod fight_engine;
+pub mod fight_log;
 pub mod loot_engine;
 pub mod powerup;
 
 pub use {
-    battlecard::*, concrete_powerup::*, effect::*, fight_engine::*, loot_engine::*, powerup::*,
+    battlecard::*, concrete_powerup::*, effect::*, environment::*, fight_engine::*, fight_log::*,
+    loot_engine::*, powerup::*,
 };
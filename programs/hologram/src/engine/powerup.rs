@@ -1,11 +1,14 @@
 use {
     super::Effect,
     crate::{
-        engine::ConditionFn,
-        state::{Bonuses, Drone, DroneClass, DroneSize, Module, ModuleClass, Mutation, Passive},
+        engine::Condition,
+        state::{
+            Bonuses, Drone, DroneClass, DroneSize, MetabolicEffect, Module, ModuleClass, Mutation,
+            MutationClass, Passive, ShipModifier, Targeting, Trigger,
+        },
         utils::LimitedString,
+        DRONE_ENERGY_COST, JAMMER_ENERGY_COST, REPAIRER_ENERGY_COST,
     },
-    std::sync::Arc,
 };
 
 // tag trait for Modules, Drones and Mutations
@@ -17,10 +20,18 @@ pub trait PowerUp {
     fn get_charge_time(&self) -> Option<u8>;
     // reactivation delay mechanism for passive modules
     fn get_heat(&self) -> Option<u8>;
-    // get what happen on activation
-    fn get_effect(&self) -> Effect;
+    // get what happen on activation (None for powerups that only provide bonuses/modifiers)
+    fn get_effect(&self) -> Option<Effect>;
     // get bonuses
     fn get_bonuses(&self) -> Option<Bonuses>;
+    // get modifiers applied to the whole spaceship
+    fn get_modifier(&self) -> Option<ShipModifier>;
+    // for reactive power-ups, what they react to (instead of being checked every turn)
+    fn get_trigger(&self) -> Option<Trigger>;
+    // for point defense power-ups, the chance to intercept each incoming missile shot
+    fn get_intercept_chance(&self) -> Option<u8>;
+    // for active power-ups, the capacitor energy drained on activation
+    fn get_energy_cost(&self) -> Option<u8>;
     fn get_kind(&self) -> PowerupKind;
 }
 
@@ -31,25 +42,38 @@ impl PowerUp for Module {
 
     fn is_active(&self) -> bool {
         match &self.class {
-            ModuleClass::Weapon(_) | ModuleClass::Repairer(_, _) | ModuleClass::Jammer(_, _) => {
-                true
-            }
-            ModuleClass::Capacitative(_, _) => false,
+            ModuleClass::Weapon(_)
+            | ModuleClass::AntiDrone(_)
+            | ModuleClass::Repairer(_, _)
+            | ModuleClass::Jammer(_, _)
+            | ModuleClass::Disruptor(_) => true,
+            ModuleClass::Capacitative(_, _)
+            | ModuleClass::Reactive(_, _)
+            | ModuleClass::PointDefense(_, _)
+            | ModuleClass::Capacitor(_, _)
+            | ModuleClass::Upgrade(_)
+            | ModuleClass::Afterburner(_, _) => false,
         }
     }
 
     fn get_charge_time(&self) -> Option<u8> {
         match &self.class {
-            ModuleClass::Weapon(wms) => Some(wms.charge_time as u8),
+            ModuleClass::Weapon(wms) | ModuleClass::AntiDrone(wms) => Some(wms.charge_time as u8),
             ModuleClass::Repairer(_, rms) => Some(rms.charge_time as u8),
             ModuleClass::Capacitative(_, _) => None,
             ModuleClass::Jammer(_, jms) => Some(jms.charge_time as u8),
+            ModuleClass::Disruptor(sms) => Some(sms.charge_time),
+            ModuleClass::Reactive(_, _) => None,
+            ModuleClass::PointDefense(_, _) => None,
+            ModuleClass::Capacitor(_, _)
+            | ModuleClass::Upgrade(_)
+            | ModuleClass::Afterburner(_, _) => None,
         }
     }
 
     fn get_heat(&self) -> Option<u8> {
         match &self.class {
-            ModuleClass::Weapon(_) => None,
+            ModuleClass::Weapon(_) | ModuleClass::AntiDrone(_) => None,
             ModuleClass::Repairer(_, _) => None,
             ModuleClass::Capacitative(_, passive) => {
                 let Passive::CapacitativeRepair {
@@ -61,15 +85,35 @@ impl PowerUp for Module {
                 Some(*heat)
             }
             ModuleClass::Jammer(_, _) => None,
+            ModuleClass::Disruptor(_) => None,
+            ModuleClass::Reactive(_, rms) => Some(rms.heat),
+            ModuleClass::PointDefense(_, pds) => Some(pds.heat),
+            ModuleClass::Capacitor(_, _)
+            | ModuleClass::Upgrade(_)
+            | ModuleClass::Afterburner(_, _) => None,
         }
     }
 
-    fn get_effect(&self) -> Effect {
-        match &self.class {
+    fn get_effect(&self) -> Option<Effect> {
+        let mut effect = match &self.class {
+            // point defense only acts when missiles come in
+            ModuleClass::PointDefense(_, _) => return None,
+            ModuleClass::Capacitor(_, _)
+            | ModuleClass::Upgrade(_)
+            | ModuleClass::Afterburner(_, _) => return None,
             ModuleClass::Weapon(wms) => Effect::Fire {
                 damage: wms.damage,
+                max_damage: wms.max_damage,
+                shots: wms.shots,
+                weapon_type: wms.weapon_type,
+                targeting: Targeting::Ship,
+            },
+            ModuleClass::AntiDrone(wms) => Effect::Fire {
+                damage: wms.damage,
+                max_damage: wms.max_damage,
                 shots: wms.shots,
                 weapon_type: wms.weapon_type,
+                targeting: Targeting::DronesFirst,
             },
             ModuleClass::Repairer(_, rms) => Effect::Repair {
                 target: rms.target,
@@ -82,11 +126,8 @@ impl PowerUp for Module {
                     repair_amount,
                     target,
                 } = &passive;
-                let threshold_clone = threshold.clone();
                 Effect::Conditionnal {
-                    condition: ConditionFn {
-                        func: Arc::new(move |sbc| sbc.recent_hull_damage() >= threshold_clone),
-                    },
+                    condition: Condition::RecentHullDamageAtLeast(*threshold),
                     effect: Box::new(Effect::Repair {
                         target: *target,
                         amount: *repair_amount,
@@ -95,16 +136,64 @@ impl PowerUp for Module {
             }
             ModuleClass::Jammer(_, jms) => Effect::Jam {
                 charge_burn: jms.charge_burn,
+                capacitor_drain: jms.capacitor_drain,
             },
-        }
+            ModuleClass::Disruptor(sms) => Effect::ApplyStatus {
+                status: sms.status,
+                target: sms.target,
+                duration: sms.duration,
+            },
+            ModuleClass::Reactive(_, rms) => rms.effect.clone(),
+        };
+        effect.increase_potency(self.tier.potency_bonus());
+        Some(effect)
     }
 
     fn get_bonuses(&self) -> Option<Bonuses> {
         match &self.class {
-            ModuleClass::Weapon(_) => None,
+            ModuleClass::Weapon(_) | ModuleClass::AntiDrone(_) | ModuleClass::Disruptor(_) => None,
             ModuleClass::Repairer(bonuses, _)
             | ModuleClass::Capacitative(bonuses, _)
-            | ModuleClass::Jammer(bonuses, _) => Some(bonuses.clone()),
+            | ModuleClass::Jammer(bonuses, _)
+            | ModuleClass::Reactive(bonuses, _)
+            | ModuleClass::PointDefense(bonuses, _)
+            | ModuleClass::Capacitor(bonuses, _)
+            | ModuleClass::Upgrade(bonuses)
+            | ModuleClass::Afterburner(bonuses, _) => Some(bonuses.clone()),
+        }
+    }
+
+    fn get_modifier(&self) -> Option<ShipModifier> {
+        None
+    }
+
+    fn get_trigger(&self) -> Option<Trigger> {
+        match &self.class {
+            ModuleClass::Reactive(_, rms) => Some(rms.trigger),
+            _ => None,
+        }
+    }
+
+    fn get_intercept_chance(&self) -> Option<u8> {
+        match &self.class {
+            ModuleClass::PointDefense(_, pds) => Some(pds.intercept_chance),
+            _ => None,
+        }
+    }
+
+    fn get_energy_cost(&self) -> Option<u8> {
+        match &self.class {
+            ModuleClass::Weapon(wms) | ModuleClass::AntiDrone(wms) => {
+                Some(wms.weapon_type.energy_cost())
+            }
+            ModuleClass::Repairer(_, _) => Some(REPAIRER_ENERGY_COST),
+            ModuleClass::Jammer(_, _) | ModuleClass::Disruptor(_) => Some(JAMMER_ENERGY_COST),
+            ModuleClass::Capacitative(_, _)
+            | ModuleClass::Reactive(_, _)
+            | ModuleClass::PointDefense(_, _)
+            | ModuleClass::Capacitor(_, _)
+            | ModuleClass::Upgrade(_)
+            | ModuleClass::Afterburner(_, _) => None,
         }
     }
 
@@ -122,42 +211,85 @@ impl PowerUp for Drone {
 
     fn is_active(&self) -> bool {
         match &self.class {
-            DroneClass::Weapon(_) | DroneClass::ECM(_) => true,
+            DroneClass::Weapon(_) | DroneClass::ECM(_) | DroneClass::Interceptor(_) => true,
+            DroneClass::PointDefense(_) => false,
         }
     }
 
     fn get_charge_time(&self) -> Option<u8> {
         match &self.class {
-            DroneClass::Weapon(wms) => Some(wms.charge_time as u8),
+            DroneClass::Weapon(wms) | DroneClass::Interceptor(wms) => Some(wms.charge_time as u8),
             DroneClass::ECM(jms) => Some(jms.charge_time as u8),
+            DroneClass::PointDefense(_) => None,
         }
     }
 
     fn get_heat(&self) -> Option<u8> {
-        None
+        match &self.class {
+            DroneClass::PointDefense(pds) => Some(pds.heat),
+            _ => None,
+        }
     }
 
-    fn get_effect(&self) -> Effect {
-        match &self.class {
+    fn get_effect(&self) -> Option<Effect> {
+        let mut effect = match &self.class {
             DroneClass::Weapon(wms) => Effect::Fire {
                 damage: wms.damage,
+                max_damage: wms.max_damage,
+                shots: wms.shots,
+                weapon_type: wms.weapon_type,
+                targeting: Targeting::Ship,
+            },
+            DroneClass::Interceptor(wms) => Effect::Fire {
+                damage: wms.damage,
+                max_damage: wms.max_damage,
                 shots: wms.shots,
                 weapon_type: wms.weapon_type,
+                targeting: Targeting::DronesFirst,
             },
             DroneClass::ECM(jms) => Effect::Jam {
                 charge_burn: jms.charge_burn,
+                capacitor_drain: jms.capacitor_drain,
             },
-        }
+            DroneClass::PointDefense(_) => return None,
+        };
+        effect.increase_potency(self.tier.potency_bonus());
+        Some(effect)
     }
 
     fn get_bonuses(&self) -> Option<Bonuses> {
         None
     }
 
+    fn get_modifier(&self) -> Option<ShipModifier> {
+        None
+    }
+
+    fn get_trigger(&self) -> Option<Trigger> {
+        None
+    }
+
+    fn get_intercept_chance(&self) -> Option<u8> {
+        match &self.class {
+            DroneClass::PointDefense(pds) => Some(pds.intercept_chance),
+            _ => None,
+        }
+    }
+
+    // drones draw little from the mothership capacitor
+    fn get_energy_cost(&self) -> Option<u8> {
+        match &self.class {
+            DroneClass::Weapon(_) | DroneClass::Interceptor(_) | DroneClass::ECM(_) => {
+                Some(DRONE_ENERGY_COST)
+            }
+            DroneClass::PointDefense(_) => None,
+        }
+    }
+
     fn get_kind(&self) -> PowerupKind {
         PowerupKind::Drone {
             class: self.class.clone(),
-            size: self.size.clone(),
+            size: self.size,
         }
     }
 }
@@ -166,23 +298,74 @@ impl PowerUp for Mutation {
     fn get_name(&self) -> LimitedString {
         self.name.clone()
     }
+
+    // mutations are all passives
     fn is_active(&self) -> bool {
-        panic!("Not implemented")
+        false
     }
+
     fn get_charge_time(&self) -> Option<u8> {
-        panic!("Not implemented")
+        None
     }
+
     fn get_heat(&self) -> Option<u8> {
-        panic!("Not implemented")
+        match &self.class {
+            MutationClass::Metabolic(_, mms) => Some(mms.heat),
+            MutationClass::Growth(_) | MutationClass::Adaptive(_, _) => None,
+        }
     }
-    fn get_effect(&self) -> Effect {
-        panic!("Not implemented")
+
+    fn get_effect(&self) -> Option<Effect> {
+        match &self.class {
+            MutationClass::Metabolic(_, mms) => match mms.effect {
+                MetabolicEffect::Repair { target, amount } => {
+                    Some(Effect::Repair { target, amount })
+                }
+                MetabolicEffect::Status {
+                    status,
+                    target,
+                    duration,
+                } => Some(Effect::ApplyStatus {
+                    status,
+                    target,
+                    duration,
+                }),
+            },
+            MutationClass::Growth(_) | MutationClass::Adaptive(_, _) => None,
+        }
     }
+
     fn get_bonuses(&self) -> Option<Bonuses> {
-        panic!("Not implemented")
+        match &self.class {
+            MutationClass::Growth(bonuses)
+            | MutationClass::Metabolic(bonuses, _)
+            | MutationClass::Adaptive(bonuses, _) => Some(bonuses.clone()),
+        }
+    }
+
+    fn get_modifier(&self) -> Option<ShipModifier> {
+        match &self.class {
+            MutationClass::Adaptive(_, modifier) => Some(*modifier),
+            MutationClass::Growth(_) | MutationClass::Metabolic(_, _) => None,
+        }
+    }
+
+    fn get_trigger(&self) -> Option<Trigger> {
+        None
+    }
+
+    fn get_intercept_chance(&self) -> Option<u8> {
+        None
+    }
+
+    fn get_energy_cost(&self) -> Option<u8> {
+        None
     }
+
     fn get_kind(&self) -> PowerupKind {
-        PowerupKind::Mutation
+        PowerupKind::Mutation {
+            class: self.class.clone(),
+        }
     }
 }
 
@@ -190,5 +373,5 @@ impl PowerUp for Mutation {
 pub enum PowerupKind {
     Module { class: ModuleClass },
     Drone { class: DroneClass, size: DroneSize },
-    Mutation,
+    Mutation { class: MutationClass },
 }
@@ -55,4 +55,28 @@ pub enum HologramError {
     MaxOrdnanceReached,
     #[msg("The game state does not permit this action")]
     InvalidAction,
+    #[msg("The drone bay can't fit this drone")]
+    DroneBayFull,
+    #[msg("There is no slot or power left to fit this module")]
+    NoRoomToFitModule,
+    #[msg("There is no power-up at this index")]
+    InvalidPowerUpIndex,
+    #[msg("The gambits are invalid")]
+    InvalidGambits,
+    #[msg("Only the realm admin can perform this action")]
+    NotRealmAdmin,
+    #[msg("The loot table entry is invalid")]
+    InvalidLootEntry,
+    #[msg("The crate odds must total 100")]
+    InvalidCrateOdds,
+    #[msg("The spaceship doesn't own a mutation of this family")]
+    MutationFamilyNotOwned,
+    #[msg("Only modules and drones can be upgraded")]
+    PowerUpNotUpgradable,
+    #[msg("The power-up is already at the highest tier")]
+    MaxTierReached,
+    #[msg("The cargo hold item isn't a copy of the upgraded power-up")]
+    InvalidUpgradeDuplicate,
+    #[msg("The fight events couldn't be hashed")]
+    EventStreamHashFailed,
 }
@@ -0,0 +1,50 @@
+use {
+    crate::{
+        error::HologramError,
+        state::{LootEntry, LootTable, Realm},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct AddLootEntry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+        constraint = realm.admin == admin.key() @ HologramError::NotRealmAdmin,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    // Note: Resize for one more entry
+    #[account(
+        mut,
+        realloc = LootTable::space(loot_table.entries.len() + 1, loot_table.crate_odds.len()),
+        realloc::payer = admin,
+        realloc::zero = false,
+        seeds=[b"loot_table", realm.key().as_ref()],
+        bump = loot_table.bump,
+    )]
+    pub loot_table: Box<Account<'info, LootTable>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct LootEntryAdded {
+    pub realm_name: String,
+    pub entry: LootEntry,
+}
+
+pub fn add_loot_entry(ctx: Context<AddLootEntry>, entry: LootEntry) -> Result<()> {
+    ctx.accounts.loot_table.add_entry(entry.clone())?;
+
+    emit!(LootEntryAdded {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        entry,
+    });
+
+    Ok(())
+}
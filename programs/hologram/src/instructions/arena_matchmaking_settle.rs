@@ -5,7 +5,7 @@ use switchboard_solana::FunctionRequestAccountData;
 use {
     super::user_facing::Faction,
     crate::{
-        engine::{FightEngine, FightOutcome, SpaceShipBattleCard},
+        engine::{Environment, FightEngine, FightLoadout, FightOutcome, SpaceShipBattleCard},
         error::HologramError,
         state::{
             spaceship, MatchmakingQueue, Realm, SpaceShip, SpaceShipLite,
@@ -90,6 +90,13 @@ pub struct ArenaMatchmakingMatchCompleted {
     pub outcome: FightOutcome,
     pub spaceship: SpaceShipLite,
     pub opponent_spaceship: SpaceShipLite,
+    // enough to verify the match off-chain with FightEngine::replay
+    // Note: the loadouts are taken before the fight, the spaceships above are the post-match accounts
+    pub spaceship_loadout: FightLoadout,
+    pub opponent_spaceship_loadout: FightLoadout,
+    pub seed: u32,
+    pub environment: Environment,
+    pub event_stream_hash: [u8; 32],
 }
 
 pub fn arena_matchmaking_settle(
@@ -138,7 +145,8 @@ pub fn arena_matchmaking_settle(
         ctx.accounts.spaceship.arena_matchmaking.matchmaking_status = MatchMakingStatus::None;
     }
 
-    // pick the opponent spaceship based on the random seed
+    // pick the opponent spaceship and the environment of the match based on the random seed
+    let environment;
     let mut opponent_spaceship = {
         let spaceship = &mut ctx.accounts.spaceship;
         let mut rng = RandomNumberGenerator::new(generated_seed.into());
@@ -147,6 +155,7 @@ pub fn arena_matchmaking_settle(
             .realm
             .get_matching_matchmaking_queue_mut(spaceship)?;
         let opponent_spaceship_key = roll_opponent_spaceship(rng.borrow_mut(), queue)?;
+        environment = Environment::roll(rng.borrow_mut());
 
         // load the opponent spaceship based on the key
         let opponent_spaceship = match opponent_spaceship_key {
@@ -209,10 +218,15 @@ pub fn arena_matchmaking_settle(
     #[cfg(any(test, feature = "testing"))]
     let mut fight_engine = FightEngine::new(event_handler);
 
+    // snapshot what the fight is played from, rewards and analytics update the spaceships right after
+    let spaceship_loadout = FightLoadout::new(spaceship);
+    let opponent_spaceship_loadout = FightLoadout::new(opponent_spaceship);
+
     // generate SpaceShipBattleCards, another data-representation of a SpaceShip object optimized for battle (injected to help with test, seems silly here)
     let outcome = fight_engine.fight(
-        &mut SpaceShipBattleCard::new(&spaceship),
-        &mut SpaceShipBattleCard::new(&opponent_spaceship),
+        &mut SpaceShipBattleCard::from_loadout(&spaceship_loadout),
+        &mut SpaceShipBattleCard::from_loadout(&opponent_spaceship_loadout),
+        environment,
         generated_seed,
         MATCH_MAX_TURN,
     );
@@ -245,6 +259,11 @@ pub fn arena_matchmaking_settle(
         outcome,
         spaceship: SpaceShipLite::from_spaceship_account(spaceship),
         opponent_spaceship: SpaceShipLite::from_spaceship_account(opponent_spaceship),
+        spaceship_loadout,
+        opponent_spaceship_loadout,
+        seed: generated_seed,
+        environment,
+        event_stream_hash: fight_engine.event_stream_hash()?,
     });
 
     #[cfg(target_os = "solana")]
@@ -280,6 +299,9 @@ pub fn roll_opponent_spaceship(
 pub fn print_event(event: BattleEvent) {
     match event {
         BattleEvent::MatchStarted { .. } => msg!("- [Match started] ----------"),
+        BattleEvent::EnvironmentRevealed { environment } => {
+            msg!("- [Environment] {:?}", environment)
+        }
         BattleEvent::TurnStart { turn } => msg!("- [Turn {}] -------------", turn),
         BattleEvent::MatchEnded { .. } => msg!("- [Match ended] ----------"),
         BattleEvent::Fire {
@@ -305,6 +327,9 @@ pub fn print_event(event: BattleEvent) {
         BattleEvent::HullDamaged { origin_id, damage } => {
             msg!("  - [{}] Takes {} Hull damages", origin_id, damage)
         }
+        BattleEvent::ArmorDamaged { origin_id, damage } => {
+            msg!("  - [{}] Takes {} Armor damages", origin_id, damage)
+        }
         BattleEvent::ShieldLayerDown { origin_id } => {
             msg!("  - [{}] Has lost one shield layer", origin_id)
         }
@@ -357,5 +382,166 @@ pub fn print_event(event: BattleEvent) {
             repair_target,
             amount
         ),
+        BattleEvent::PowerUpActivated {
+            origin_id,
+            powerup_index,
+        } => msg!("  - [{}] Activates powerup #{}", origin_id, powerup_index),
+        BattleEvent::PassiveTriggered {
+            origin_id,
+            powerup_index,
+        } => msg!(
+            "  - [{}] Passive powerup #{} triggered",
+            origin_id,
+            powerup_index
+        ),
+        BattleEvent::StatusApplied {
+            origin_id,
+            status,
+            remaining_turns,
+            stacks,
+        } => msg!(
+            "  - [{}] Affected by {} x{} for {} turns",
+            origin_id,
+            status,
+            stacks,
+            remaining_turns
+        ),
+        BattleEvent::StatusExpired { origin_id, status } => {
+            msg!("  - [{}] {} wore off", origin_id, status)
+        }
+        BattleEvent::PowerUpReacted {
+            origin_id,
+            powerup_index,
+            trigger,
+        } => msg!(
+            "  - [{}] Powerup #{} reacts ({:?})",
+            origin_id,
+            powerup_index,
+            trigger
+        ),
+        BattleEvent::DroneDamaged {
+            origin_id,
+            powerup_index,
+            damage,
+        } => msg!(
+            "  - [{}] Drone #{} takes {} damages",
+            origin_id,
+            powerup_index,
+            damage
+        ),
+        BattleEvent::DroneDestroyed {
+            origin_id,
+            powerup_index,
+        } => msg!("  - [{}] Drone #{} shot down", origin_id, powerup_index),
+        BattleEvent::EscalationStarted {} => msg!("- [Escalation] shields stop regenerating"),
+        BattleEvent::Attrition { origin_id, damage } => {
+            msg!(
+                "  - [{}] Attrition deals {} Hull damages",
+                origin_id,
+                damage
+            )
+        }
+        BattleEvent::TieBroken {
+            tie_breaker,
+            outcome,
+        } => msg!("- [Tie broken on {:?}] {:?}", tie_breaker, outcome),
+        BattleEvent::SpaceShipDefeated { origin_id } => msg!("  - [{}] Defeated", origin_id),
+        BattleEvent::MissileIntercepted {
+            origin_id,
+            powerup_index,
+        } => msg!(
+            "  - [{}] Powerup #{} intercepted a missile",
+            origin_id,
+            powerup_index
+        ),
+        BattleEvent::CapacitorDrained {
+            origin_id,
+            target_id,
+            amount,
+        } => msg!(
+            "  - [{}] Drained {} energy from [{}] capacitor",
+            origin_id,
+            amount,
+            target_id
+        ),
+        BattleEvent::CriticalHit {
+            origin_id,
+            target_id,
+            damage,
+        } => msg!(
+            "  - [{}] Critical hit on [{}] for {} damage",
+            origin_id,
+            target_id,
+            damage
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{engine::LT_MODULES_COMMON, state::mock_spaceship, utils::LimitedString},
+    };
+
+    #[test]
+    fn test_replay_from_match_completed_event() {
+        let module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let mut spaceship = mock_spaceship(vec![module.clone()], vec![], vec![]);
+        let opponent_spaceship = mock_spaceship(vec![module.clone(), module], vec![], vec![]);
+        let seed = 42;
+        let environment = Environment::Nebula;
+
+        // same steps as the settlement
+        let spaceship_loadout = FightLoadout::new(&spaceship);
+        let opponent_spaceship_loadout = FightLoadout::new(&opponent_spaceship);
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        let outcome = fight_engine.fight(
+            &mut SpaceShipBattleCard::from_loadout(&spaceship_loadout),
+            &mut SpaceShipBattleCard::from_loadout(&opponent_spaceship_loadout),
+            environment,
+            seed,
+            MATCH_MAX_TURN,
+        );
+        // the spaceship changing after the fight doesn't affect what was emitted
+        spaceship.modules.clear();
+        let event = ArenaMatchmakingMatchCompleted {
+            realm_name: "realm".to_string(),
+            user: Pubkey::default(),
+            outcome,
+            spaceship: SpaceShipLite {
+                name: spaceship.name,
+                hull: spaceship.hull,
+                spaceship: Pubkey::default(),
+            },
+            opponent_spaceship: SpaceShipLite {
+                name: opponent_spaceship.name,
+                hull: opponent_spaceship.hull,
+                spaceship: Pubkey::default(),
+            },
+            spaceship_loadout,
+            opponent_spaceship_loadout,
+            seed,
+            environment,
+            event_stream_hash: fight_engine.event_stream_hash().unwrap(),
+        };
+
+        // the indexer only has the event data to replay the match
+        let event =
+            ArenaMatchmakingMatchCompleted::try_from_slice(&event.try_to_vec().unwrap()).unwrap();
+        let replay = FightEngine::replay(
+            &event.spaceship_loadout,
+            &event.opponent_spaceship_loadout,
+            event.environment,
+            event.seed,
+            MATCH_MAX_TURN,
+        )
+        .unwrap();
+        assert_eq!(replay.outcome, event.outcome);
+        assert_eq!(replay.event_stream_hash, event.event_stream_hash);
+        assert!(!replay.fight_log.entries.is_empty());
     }
 }
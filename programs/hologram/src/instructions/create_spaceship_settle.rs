@@ -125,20 +125,7 @@ pub fn create_spaceship_settle(
 
     // Roll the Hull with the first generated seed
     {
-        let dice_roll = rng.roll_dice(10); // waiting for mem::variant_count::<Hull>() to be non nightly only rust...
-        ctx.accounts.spaceship.hull = match dice_roll {
-            1 => Hull::CommonOne,
-            2 => Hull::CommonTwo,
-            3 => Hull::CommonThree,
-            4 => Hull::UncommonOne,
-            5 => Hull::UncommonTwo,
-            6 => Hull::UncommonThree,
-            7 => Hull::UncommonFour,
-            8 => Hull::RareOne,
-            9 => Hull::RareTwo,
-            10 => Hull::FactionOne,
-            _ => panic!("Invalid dice roll"),
-        };
+        ctx.accounts.spaceship.hull = Hull::roll(&mut rng);
     }
 
     // provide spaceship with starting module and credits
@@ -0,0 +1,67 @@
+use {
+    crate::{
+        error::HologramError,
+        state::{LootTable, Realm},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct InitializeLootTable<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+        constraint = realm.admin == admin.key() @ HologramError::NotRealmAdmin,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    // Note: starts with the default loot tables and crate odds
+    #[account(
+        init,
+        payer=admin,
+        seeds=[b"loot_table", realm.key().as_ref()],
+        bump,
+        space = LootTable::space(LootTable::DEFAULT_ENTRIES, LootTable::DEFAULT_CRATE_ODDS),
+    )]
+    pub loot_table: Box<Account<'info, LootTable>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct LootTableInitialized {
+    pub realm_name: String,
+    pub pda: Pubkey,
+    pub entries: u8,
+}
+
+pub fn initialize_loot_table(ctx: Context<InitializeLootTable>) -> Result<()> {
+    // Initialize LootTable account
+    {
+        let loot_table = &mut ctx.accounts.loot_table;
+        let LootTable {
+            entries,
+            crate_odds,
+            ..
+        } = LootTable::default();
+        loot_table.bump = *ctx
+            .bumps
+            .get("loot_table")
+            .ok_or(ProgramError::InvalidSeeds)?;
+        loot_table.realm = ctx.accounts.realm.key();
+        loot_table.entries = entries;
+        loot_table.crate_odds = crate_odds;
+        loot_table.validate()?;
+    }
+
+    emit!(LootTableInitialized {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        pda: ctx.accounts.loot_table.key(),
+        entries: ctx.accounts.loot_table.entries.len() as u8,
+    });
+
+    Ok(())
+}
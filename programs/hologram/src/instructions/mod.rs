@@ -1,14 +1,20 @@
 // admin instructions
+pub mod add_loot_entry;
 pub mod arena_matchmaking_settle;
 pub mod create_spaceship_settle;
+pub mod initialize_loot_table;
 pub mod initialize_realm;
 pub mod pick_crate_settle;
+pub mod remove_loot_entry;
+pub mod set_crate_odds;
+pub mod set_loot_entry_weight;
 pub mod user_facing;
 
 // public instructions
 
 // bring everything in scope
 pub use {
-    arena_matchmaking_settle::*, create_spaceship_settle::*, initialize_realm::*,
-    pick_crate_settle::*, user_facing::*,
+    add_loot_entry::*, arena_matchmaking_settle::*, create_spaceship_settle::*,
+    initialize_loot_table::*, initialize_realm::*, pick_crate_settle::*, remove_loot_entry::*,
+    set_crate_odds::*, set_loot_entry_weight::*, user_facing::*,
 };
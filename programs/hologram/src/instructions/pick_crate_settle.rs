@@ -2,12 +2,12 @@
 use switchboard_solana::FunctionRequestAccountData;
 use {
     crate::{
-        engine::LootEngine,
+        engine::{LootEngine, MutationDrop},
         error::HologramError,
         instructions::user_facing::PickCrateSuccess,
         state::{
-            Currency, Realm, SpaceShip, SpaceShipLite, SwitchboardFunctionRequestStatus,
-            UserAccount,
+            CrateOdds, Currency, LootTable, Realm, SpaceShip, SpaceShipLite,
+            SwitchboardFunctionRequestStatus, UserAccount,
         },
         utils::RandomNumberGenerator,
         MAX_ORDNANCE,
@@ -18,6 +18,7 @@ use {
 };
 
 // total of each category must be 100 (%)
+// Note: these are the defaults of the realm LootTable, the admin can tune them afterward
 
 pub const NI_CURRENCY: Currency = Currency::ImperialCredit;
 pub const NI_PRICE: u8 = 25;
@@ -26,6 +27,8 @@ pub const NI_MODULE_CHANCE: u8 = 80;
 pub const NI_DRONE_CHANCE: u8 = 20;
 pub const NI_MUTATION_CHANCE: u8 = 0;
 pub const NI_SCAM_CHANCE: u8 = 0;
+pub const NI_PITY_THRESHOLD: u8 = 8;
+pub const NI_PITY_RARITY_BONUS: u8 = 10;
 pub const NI_FACTION_RARITY_ENABLED: bool = false;
 
 pub const PC_CURRENCY: Currency = Currency::ImperialCredit;
@@ -35,6 +38,8 @@ pub const PC_MODULE_CHANCE: u8 = 45;
 pub const PC_DRONE_CHANCE: u8 = 42;
 pub const PC_MUTATION_CHANCE: u8 = 5;
 pub const PC_SCAM_CHANCE: u8 = 8;
+pub const PC_PITY_THRESHOLD: u8 = 10;
+pub const PC_PITY_RARITY_BONUS: u8 = 8;
 pub const PC_FACTION_RARITY_ENABLED: bool = true;
 
 pub const BMC_CURRENCY: Currency = Currency::ActivateNanitePaste;
@@ -44,6 +49,8 @@ pub const BMC_MODULE_CHANCE: u8 = 40;
 pub const BMC_DRONE_CHANCE: u8 = 15;
 pub const BMC_MUTATION_CHANCE: u8 = 40;
 pub const BMC_SCAM_CHANCE: u8 = 5;
+pub const BMC_PITY_THRESHOLD: u8 = 10;
+pub const BMC_PITY_RARITY_BONUS: u8 = 8;
 pub const BMC_FACTION_RARITY_ENABLED: bool = false;
 
 #[derive(Accounts)]
@@ -68,6 +75,12 @@ pub struct PickCrateSettle<'info> {
     )]
     pub user_account: Account<'info, UserAccount>,
 
+    #[account(
+        seeds=[b"loot_table", realm.key().as_ref()],
+        bump = loot_table.bump,
+    )]
+    pub loot_table: Box<Account<'info, LootTable>>,
+
     // Note: The spaceship is pre-resized in pick_crate, where we always realloc a space for each type of power-up preemptively
     //  if we were to do so here, it would complicate things with the SBf() payer
     #[account(
@@ -103,7 +116,7 @@ pub fn pick_crate_settle(
     {
         // verify that the user Ordnance is not maxxed (redundant, done in pick_crate, but for safety)
         require!(
-            ctx.accounts.spaceship.ordnance() < MAX_ORDNANCE,
+            ctx.accounts.spaceship.owned_powerups() < MAX_ORDNANCE,
             HologramError::MaxOrdnanceReached
         );
 
@@ -150,24 +163,67 @@ pub fn pick_crate_settle(
         let mut rng = RandomNumberGenerator::new(generated_seed as u64);
         let crate_outcome_roll = rng.roll_dice(100) as u8;
 
-        let crate_outcome = crate_type.determine_outcome(crate_outcome_roll);
+        let loot_table = &ctx.accounts.loot_table;
+        let crate_odds = loot_table.get_crate_odds(crate_type)?;
+        let crate_outcome = crate_odds.determine_outcome(crate_outcome_roll);
         let spaceship = ctx.accounts.spaceship.borrow_mut();
+        // the longer the spaceship goes without a Rare from this crate type, the better the odds
+        let pity_bonus = crate_odds.pity_bonus(spaceship.crate_picking.pity_counter(crate_type));
         match crate_outcome {
             CrateOutcome::Module {
                 faction_rarity_enabled,
             } => {
-                let module = LootEngine::drop_module(&mut rng, faction_rarity_enabled)?;
+                let module = LootEngine::drop_module(
+                    &mut rng,
+                    loot_table,
+                    faction_rarity_enabled,
+                    pity_bonus,
+                )?;
+                spaceship
+                    .crate_picking
+                    .record_drop(crate_type, module.rarity);
                 spaceship.mount_module(module)?;
             }
             CrateOutcome::Drone {
                 faction_rarity_enabled,
             } => {
-                let drone = LootEngine::drop_drone(&mut rng, faction_rarity_enabled)?;
+                let drone = LootEngine::drop_drone(
+                    &mut rng,
+                    loot_table,
+                    faction_rarity_enabled,
+                    pity_bonus,
+                )?;
+                spaceship
+                    .crate_picking
+                    .record_drop(crate_type, drone.rarity);
                 spaceship.load_drone(drone)?;
             }
             CrateOutcome::Mutation => {
-                let mutation = LootEngine::drop_mutation(&mut rng, &spaceship.mutations)?;
-                spaceship.apply_mutation(mutation)?;
+                match LootEngine::drop_mutation(
+                    &mut rng,
+                    loot_table,
+                    &spaceship.owned_mutations(),
+                    pity_bonus,
+                )? {
+                    MutationDrop::New(mutation) => {
+                        spaceship
+                            .crate_picking
+                            .record_drop(crate_type, mutation.rarity);
+                        spaceship.apply_mutation(mutation)?;
+                    }
+                    MutationDrop::Evolution(mutation) => {
+                        spaceship
+                            .crate_picking
+                            .record_drop(crate_type, mutation.rarity);
+                        spaceship.evolve_mutation(mutation)?;
+                    }
+                    MutationDrop::Exhausted => {
+                        msg!("Nothing left to mutate, the crate is refunded");
+                        spaceship
+                            .wallet
+                            .credit(crate_type.crate_price(), crate_type.payment_currency())?;
+                    }
+                }
             }
             CrateOutcome::Scam => {
                 msg!("You've been scammed...");
@@ -192,13 +248,18 @@ pub fn pick_crate_settle(
             spaceship: SpaceShipLite::from_spaceship_account(&ctx.accounts.spaceship),
             crate_type,
             seed: generated_seed,
+            pity_counter: ctx
+                .accounts
+                .spaceship
+                .crate_picking
+                .pity_counter(crate_type),
         });
 
         Ok(())
     }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
 pub enum CrateType {
     NavyIssue,
     PirateContraband,
@@ -231,59 +292,25 @@ pub enum CrateOutcome {
     Scam, // no drop
 }
 
-impl CrateType {
+impl CrateOdds {
     // Determine the outcome of the crate based on the roll
     pub fn determine_outcome(&self, roll: u8) -> CrateOutcome {
-        let crate_chances = match self {
-            CrateType::NavyIssue => [
-                (
-                    NI_MODULE_CHANCE,
-                    CrateOutcome::Module {
-                        faction_rarity_enabled: NI_FACTION_RARITY_ENABLED,
-                    },
-                ),
-                (
-                    NI_DRONE_CHANCE,
-                    CrateOutcome::Drone {
-                        faction_rarity_enabled: NI_FACTION_RARITY_ENABLED,
-                    },
-                ),
-                (NI_MUTATION_CHANCE, CrateOutcome::Mutation),
-                (NI_SCAM_CHANCE, CrateOutcome::Scam),
-            ],
-            CrateType::PirateContraband => [
-                (
-                    PC_MODULE_CHANCE,
-                    CrateOutcome::Module {
-                        faction_rarity_enabled: PC_FACTION_RARITY_ENABLED,
-                    },
-                ),
-                (
-                    PC_DRONE_CHANCE,
-                    CrateOutcome::Drone {
-                        faction_rarity_enabled: PC_FACTION_RARITY_ENABLED,
-                    },
-                ),
-                (PC_MUTATION_CHANCE, CrateOutcome::Mutation),
-                (PC_SCAM_CHANCE, CrateOutcome::Scam),
-            ],
-            CrateType::BiomechanicalCache => [
-                (
-                    BMC_MODULE_CHANCE,
-                    CrateOutcome::Module {
-                        faction_rarity_enabled: BMC_FACTION_RARITY_ENABLED,
-                    },
-                ),
-                (
-                    BMC_DRONE_CHANCE,
-                    CrateOutcome::Drone {
-                        faction_rarity_enabled: BMC_FACTION_RARITY_ENABLED,
-                    },
-                ),
-                (BMC_MUTATION_CHANCE, CrateOutcome::Mutation),
-                (BMC_SCAM_CHANCE, CrateOutcome::Scam),
-            ],
-        };
+        let crate_chances = [
+            (
+                self.module_weight,
+                CrateOutcome::Module {
+                    faction_rarity_enabled: self.faction_rarity_enabled,
+                },
+            ),
+            (
+                self.drone_weight,
+                CrateOutcome::Drone {
+                    faction_rarity_enabled: self.faction_rarity_enabled,
+                },
+            ),
+            (self.mutation_weight, CrateOutcome::Mutation),
+            (self.scam_weight, CrateOutcome::Scam),
+        ];
 
         let mut cumulative_chance = 0;
         for (chance, outcome) in crate_chances.iter() {
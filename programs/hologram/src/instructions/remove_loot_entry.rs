@@ -0,0 +1,51 @@
+use {
+    crate::{
+        error::HologramError,
+        state::{LootEntry, LootTable, Realm},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct RemoveLootEntry<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+        constraint = realm.admin == admin.key() @ HologramError::NotRealmAdmin,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    // Note: Shrink by one entry, the rent excess goes back to the admin
+    #[account(
+        mut,
+        realloc = LootTable::space(loot_table.entries.len().saturating_sub(1), loot_table.crate_odds.len()),
+        realloc::payer = admin,
+        realloc::zero = false,
+        seeds=[b"loot_table", realm.key().as_ref()],
+        bump = loot_table.bump,
+    )]
+    pub loot_table: Box<Account<'info, LootTable>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct LootEntryRemoved {
+    pub realm_name: String,
+    pub entry: LootEntry,
+}
+
+// Note: fails if it would leave a rarity bucket that a crate can roll empty
+pub fn remove_loot_entry(ctx: Context<RemoveLootEntry>, index: u8) -> Result<()> {
+    let entry = ctx.accounts.loot_table.remove_entry(index)?;
+
+    emit!(LootEntryRemoved {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        entry,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use {
+    crate::{
+        error::HologramError,
+        state::{CrateOdds, LootTable, Realm},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetCrateOdds<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+        constraint = realm.admin == admin.key() @ HologramError::NotRealmAdmin,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    // Note: the table is initialized with the odds of every CrateType, no resize needed
+    #[account(
+        mut,
+        seeds=[b"loot_table", realm.key().as_ref()],
+        bump = loot_table.bump,
+    )]
+    pub loot_table: Box<Account<'info, LootTable>>,
+}
+
+#[event]
+pub struct CrateOddsSet {
+    pub realm_name: String,
+    pub crate_odds: CrateOdds,
+}
+
+pub fn set_crate_odds(ctx: Context<SetCrateOdds>, crate_odds: CrateOdds) -> Result<()> {
+    ctx.accounts.loot_table.set_crate_odds(crate_odds)?;
+
+    emit!(CrateOddsSet {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        crate_odds,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use {
+    crate::{
+        error::HologramError,
+        state::{LootTable, Realm},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetLootEntryWeight<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+        constraint = realm.admin == admin.key() @ HologramError::NotRealmAdmin,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    #[account(
+        mut,
+        seeds=[b"loot_table", realm.key().as_ref()],
+        bump = loot_table.bump,
+    )]
+    pub loot_table: Box<Account<'info, LootTable>>,
+}
+
+#[event]
+pub struct LootEntryReweighted {
+    pub realm_name: String,
+    pub index: u8,
+    pub weight: u8,
+}
+
+pub fn set_loot_entry_weight(
+    ctx: Context<SetLootEntryWeight>,
+    index: u8,
+    weight: u8,
+) -> Result<()> {
+    ctx.accounts.loot_table.set_entry_weight(index, weight)?;
+
+    emit!(LootEntryReweighted {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        index,
+        weight,
+    });
+
+    Ok(())
+}
@@ -1,9 +1,7 @@
 use {
     crate::{
         error::HologramError,
-        state::{
-            Module, Realm, SpaceShip, SpaceShipLite, SwitchboardFunctionRequestStatus, UserAccount,
-        },
+        state::{Realm, SpaceShip, SpaceShipLite, SwitchboardFunctionRequestStatus, UserAccount},
         utils::LimitedString,
         BASE_MAX_FUEL, MAX_SPACESHIPS_PER_USER_ACCOUNT,
     },
@@ -49,7 +47,7 @@ pub struct CreateSpaceship<'info> {
         payer=user,
         seeds=[b"spaceship", realm.key().as_ref(), user.key.as_ref(), (user_account.spaceships.len() as u8).to_le_bytes().as_ref()],
         bump,
-        space = SpaceShip::LEN + std::mem::size_of::<Module>(), // make space for the starter civilian weapon module
+        space = SpaceShip::space(1, 0, 0, 0, 0), // make space for the starter civilian weapon module
     )]
     pub spaceship: Box<Account<'info, SpaceShip>>,
 
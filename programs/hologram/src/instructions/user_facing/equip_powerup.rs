@@ -0,0 +1,70 @@
+use {
+    crate::{
+        error::HologramError,
+        state::{CargoItem, Realm, SpaceShip, SpaceShipLite, UserAccount},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(spaceship_index:u8)]
+pub struct EquipPowerup<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    #[account(
+        seeds=[b"user_account", realm.key().as_ref(), user.key.as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // Note: no realloc needed, a fitted power-up takes less space than the same one in the cargo hold
+    #[account(
+        mut,
+        seeds=[b"spaceship", realm.key().as_ref(), user.key.as_ref(), spaceship_index.to_le_bytes().as_ref()],
+        bump = spaceship.bump,
+    )]
+    pub spaceship: Account<'info, SpaceShip>,
+}
+
+#[event]
+pub struct PowerupEquipped {
+    pub realm_name: String,
+    pub user: Pubkey,
+    pub spaceship: SpaceShipLite,
+    pub powerup: CargoItem,
+}
+
+pub fn equip_powerup(ctx: Context<EquipPowerup>, cargo_index: u8) -> Result<()> {
+    // Validations
+    {
+        ctx.accounts.spaceship.ensure_fitting_unlocked()?;
+    }
+
+    // move the power-up from the cargo hold to the fitting
+    let powerup = {
+        let spaceship = &mut ctx.accounts.spaceship;
+        let powerup = spaceship
+            .cargo_hold
+            .get(cargo_index as usize)
+            .cloned()
+            .ok_or(HologramError::InvalidPowerUpIndex)?;
+        spaceship.equip(cargo_index as usize)?;
+        powerup
+    };
+
+    emit!(PowerupEquipped {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        user: ctx.accounts.user.key(),
+        spaceship: SpaceShipLite::from_spaceship_account(&ctx.accounts.spaceship),
+        powerup,
+    });
+
+    Ok(())
+}
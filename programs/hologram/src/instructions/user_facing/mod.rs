@@ -2,8 +2,14 @@ pub mod arena_matchmaking;
 pub mod claim_fuel_allowance;
 pub mod create_spaceship;
 pub mod create_user_account;
+pub mod equip_powerup;
 pub mod pick_crate;
+pub mod salvage_powerup;
+pub mod set_gambits;
+pub mod unequip_powerup;
+pub mod upgrade_powerup;
 pub use {
     arena_matchmaking::*, claim_fuel_allowance::*, create_spaceship::*, create_user_account::*,
-    pick_crate::*,
+    equip_powerup::*, pick_crate::*, salvage_powerup::*, set_gambits::*, unequip_powerup::*,
+    upgrade_powerup::*,
 };
@@ -2,10 +2,7 @@ use {
     crate::{
         error::HologramError,
         instructions::{CrateType, BMC_PRICE, NI_PRICE, PC_PRICE},
-        state::{
-            Drone, Module, Mutation, Realm, SpaceShip, SpaceShipLite,
-            SwitchboardFunctionRequestStatus, UserAccount,
-        },
+        state::{Realm, SpaceShip, SpaceShipLite, SwitchboardFunctionRequestStatus, UserAccount},
         MAX_ORDNANCE,
     },
     anchor_lang::prelude::*,
@@ -33,11 +30,11 @@ pub struct PickCrate<'info> {
     )]
     pub user_account: Box<Account<'info, UserAccount>>,
 
-    // Note: Pre-emptively resize the modules/drones/mutations arrays to avoid reallocating them in the settle instruction
+    // Note: Pre-emptively resize the modules/drones/mutations/cargo_hold arrays to avoid reallocating them in the settle instruction
     // It complicates things to do so in the settle due to the payer required for reallocating
     #[account(
         mut,
-        realloc = SpaceShip::LEN + std::mem::size_of::<Module>() * (spaceship.modules.len() + 1) + std::mem::size_of::<Drone>() * (spaceship.drones.len() + 1) + std::mem::size_of::<Mutation>() * (spaceship.mutations.len() + 1),
+        realloc = SpaceShip::space(spaceship.modules.len() + 1, spaceship.drones.len() + 1, spaceship.mutations.len() + 1, spaceship.cargo_hold.len() + 1, spaceship.gambits.len()),
         realloc::payer = user,
         realloc::zero = false,
         seeds=[b"spaceship", realm.key().as_ref(), user.key.as_ref(), spaceship_index.to_le_bytes().as_ref()],
@@ -89,6 +86,8 @@ pub struct PickCrateSuccess {
     pub spaceship: SpaceShipLite,
     pub crate_type: CrateType,
     pub seed: u32,
+    // sub-Rare drops in a row from this crate type, after this pick
+    pub pity_counter: u8,
 }
 
 #[event]
@@ -123,7 +122,7 @@ pub fn pick_crate(ctx: Context<PickCrate>, crate_type: CrateType) -> Result<()>
     {
         // verify that the user Ordnance is not maxxed
         require!(
-            ctx.accounts.spaceship.ordnance() < MAX_ORDNANCE,
+            ctx.accounts.spaceship.owned_powerups() < MAX_ORDNANCE,
             HologramError::MaxOrdnanceReached
         );
 
@@ -0,0 +1,119 @@
+use {
+    crate::state::{
+        CargoItem, Currency, PowerUpLocation, PowerUpType, Rarity, Realm, SpaceShip, SpaceShipLite,
+        Tier, UserAccount,
+    },
+    anchor_lang::prelude::*,
+};
+
+// refund of a salvaged Tier I power-up, modules and drones are paid in ImperialCredits and mutations in ActivateNanitePaste
+// Note: each tier above adds the same amount again (see salvage_refund), a fraction of the upgrade price
+pub const SALVAGE_COMMON_REFUND: u8 = 5;
+pub const SALVAGE_UNCOMMON_REFUND: u8 = 10;
+pub const SALVAGE_RARE_REFUND: u8 = 15;
+pub const SALVAGE_FACTION_REFUND: u8 = 20;
+
+#[derive(Accounts)]
+#[instruction(spaceship_index:u8, location:PowerUpLocation)]
+pub struct SalvagePowerup<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    #[account(
+        seeds=[b"user_account", realm.key().as_ref(), user.key.as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // Note: Resize for one less power-up where the salvaged one was, the freed rent goes back to the user
+    #[account(
+        mut,
+        realloc = SpaceShip::space(spaceship.modules.len().saturating_sub(matches!(location, PowerUpLocation::Fitted { powerup_type: PowerUpType::Module, .. }) as usize), spaceship.drones.len().saturating_sub(matches!(location, PowerUpLocation::Fitted { powerup_type: PowerUpType::Drone, .. }) as usize), spaceship.mutations.len().saturating_sub(matches!(location, PowerUpLocation::Fitted { powerup_type: PowerUpType::Mutation, .. }) as usize), spaceship.cargo_hold.len().saturating_sub(matches!(location, PowerUpLocation::CargoHold { .. }) as usize), spaceship.gambits.len()),
+        realloc::payer = user,
+        realloc::zero = false,
+        seeds=[b"spaceship", realm.key().as_ref(), user.key.as_ref(), spaceship_index.to_le_bytes().as_ref()],
+        bump = spaceship.bump,
+    )]
+    pub spaceship: Account<'info, SpaceShip>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct PowerupSalvaged {
+    pub realm_name: String,
+    pub user: Pubkey,
+    pub spaceship: SpaceShipLite,
+    pub powerup: CargoItem,
+    pub refund: u8,
+    pub currency: Currency,
+}
+
+pub fn salvage_powerup(ctx: Context<SalvagePowerup>, location: PowerUpLocation) -> Result<()> {
+    // Validations
+    {
+        ctx.accounts.spaceship.ensure_fitting_unlocked()?;
+    }
+
+    // take the power-up off the spaceship and refund it
+    let (powerup, refund, currency) = {
+        let spaceship = &mut ctx.accounts.spaceship;
+        let powerup = spaceship.remove_powerup(location)?;
+        // mutations have no tier
+        let (rarity, tier, currency) = match &powerup {
+            CargoItem::Module(module) => (module.rarity, module.tier, Currency::ImperialCredit),
+            CargoItem::Drone(drone) => (drone.rarity, drone.tier, Currency::ImperialCredit),
+            CargoItem::Mutation(mutation) => {
+                (mutation.rarity, Tier::I, Currency::ActivateNanitePaste)
+            }
+        };
+        let refund = salvage_refund(rarity, tier);
+        spaceship.wallet.credit(refund, currency)?;
+        (powerup, refund, currency)
+    };
+
+    emit!(PowerupSalvaged {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        user: ctx.accounts.user.key(),
+        spaceship: SpaceShipLite::from_spaceship_account(&ctx.accounts.spaceship),
+        powerup,
+        refund,
+        currency,
+    });
+
+    Ok(())
+}
+
+pub fn salvage_refund(rarity: Rarity, tier: Tier) -> u8 {
+    let rarity_refund = match rarity {
+        Rarity::Common => SALVAGE_COMMON_REFUND,
+        Rarity::Uncommon => SALVAGE_UNCOMMON_REFUND,
+        Rarity::Rare => SALVAGE_RARE_REFUND,
+        Rarity::Faction => SALVAGE_FACTION_REFUND,
+    };
+    rarity_refund * (tier.potency_bonus() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salvage_refund() {
+        // rarer and upgraded power-ups are worth more
+        assert!(
+            salvage_refund(Rarity::Common, Tier::I) < salvage_refund(Rarity::Uncommon, Tier::I)
+        );
+        assert!(salvage_refund(Rarity::Rare, Tier::I) < salvage_refund(Rarity::Faction, Tier::I));
+        assert_eq!(
+            salvage_refund(Rarity::Rare, Tier::III),
+            3 * salvage_refund(Rarity::Rare, Tier::I)
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use {
+    crate::state::{Gambit, Realm, SpaceShip, SpaceShipLite, UserAccount},
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(spaceship_index:u8, gambits: Vec<Gambit>)]
+pub struct SetGambits<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    #[account(
+        seeds=[b"user_account", realm.key().as_ref(), user.key.as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // Note: Resize for the new gambits
+    #[account(
+        mut,
+        realloc = SpaceShip::space(spaceship.modules.len(), spaceship.drones.len(), spaceship.mutations.len(), spaceship.cargo_hold.len(), gambits.len()),
+        realloc::payer = user,
+        realloc::zero = false,
+        seeds=[b"spaceship", realm.key().as_ref(), user.key.as_ref(), spaceship_index.to_le_bytes().as_ref()],
+        bump = spaceship.bump,
+    )]
+    pub spaceship: Account<'info, SpaceShip>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct GambitsSet {
+    pub realm_name: String,
+    pub user: Pubkey,
+    pub spaceship: SpaceShipLite,
+    pub gambits: Vec<Gambit>,
+}
+
+pub fn set_gambits(ctx: Context<SetGambits>, gambits: Vec<Gambit>) -> Result<()> {
+    // Validations
+    {
+        ctx.accounts.spaceship.ensure_fitting_unlocked()?;
+    }
+
+    // replace the gambits (validated against the fitting)
+    {
+        ctx.accounts.spaceship.set_gambits(gambits.clone())?;
+    }
+
+    emit!(GambitsSet {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        user: ctx.accounts.user.key(),
+        spaceship: SpaceShipLite::from_spaceship_account(&ctx.accounts.spaceship),
+        gambits,
+    });
+
+    Ok(())
+}
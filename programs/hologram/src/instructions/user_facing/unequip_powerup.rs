@@ -0,0 +1,78 @@
+use {
+    crate::{
+        error::HologramError,
+        state::{CargoItem, PowerUpType, Realm, SpaceShip, SpaceShipLite, UserAccount},
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+#[instruction(spaceship_index:u8)]
+pub struct UnequipPowerup<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    #[account(
+        seeds=[b"user_account", realm.key().as_ref(), user.key.as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // Note: Resize for one more item in the cargo hold (a cargo item is slightly larger than the fitted power-up)
+    #[account(
+        mut,
+        realloc = SpaceShip::space(spaceship.modules.len(), spaceship.drones.len(), spaceship.mutations.len(), spaceship.cargo_hold.len() + 1, spaceship.gambits.len()),
+        realloc::payer = user,
+        realloc::zero = false,
+        seeds=[b"spaceship", realm.key().as_ref(), user.key.as_ref(), spaceship_index.to_le_bytes().as_ref()],
+        bump = spaceship.bump,
+    )]
+    pub spaceship: Account<'info, SpaceShip>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[event]
+pub struct PowerupUnequipped {
+    pub realm_name: String,
+    pub user: Pubkey,
+    pub spaceship: SpaceShipLite,
+    pub powerup: CargoItem,
+}
+
+pub fn unequip_powerup(
+    ctx: Context<UnequipPowerup>,
+    powerup_type: PowerUpType,
+    index: u8,
+) -> Result<()> {
+    // Validations
+    {
+        ctx.accounts.spaceship.ensure_fitting_unlocked()?;
+    }
+
+    // move the power-up from the fitting to the cargo hold
+    let powerup = {
+        let spaceship = &mut ctx.accounts.spaceship;
+        spaceship.unequip(powerup_type, index as usize)?;
+        spaceship
+            .cargo_hold
+            .last()
+            .cloned()
+            .ok_or(HologramError::InvalidPowerUpIndex)?
+    };
+
+    emit!(PowerupUnequipped {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        user: ctx.accounts.user.key(),
+        spaceship: SpaceShipLite::from_spaceship_account(&ctx.accounts.spaceship),
+        powerup,
+    });
+
+    Ok(())
+}
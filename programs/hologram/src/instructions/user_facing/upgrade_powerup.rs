@@ -0,0 +1,139 @@
+use {
+    crate::state::{
+        Currency, PowerUpType, Rarity, Realm, SpaceShip, SpaceShipLite, Tier, UserAccount,
+    },
+    anchor_lang::prelude::*,
+};
+
+pub const UPGRADE_CURRENCY: Currency = Currency::ImperialCredit;
+// price of reaching the tier, multiplied by the power-up rarity and halved when a copy is consumed (see upgrade_price)
+pub const TIER_II_UPGRADE_PRICE: u16 = 20;
+pub const TIER_III_UPGRADE_PRICE: u16 = 40;
+
+#[derive(Accounts)]
+#[instruction(spaceship_index:u8)]
+pub struct UpgradePowerup<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds=[b"realm", realm.name.to_bytes()],
+        bump = realm.bump,
+    )]
+    pub realm: Box<Account<'info, Realm>>,
+
+    #[account(
+        seeds=[b"user_account", realm.key().as_ref(), user.key.as_ref()],
+        bump = user_account.bump,
+    )]
+    pub user_account: Account<'info, UserAccount>,
+
+    // Note: no realloc needed, the tier doesn't change the size and a consumed duplicate only frees space
+    #[account(
+        mut,
+        seeds=[b"spaceship", realm.key().as_ref(), user.key.as_ref(), spaceship_index.to_le_bytes().as_ref()],
+        bump = spaceship.bump,
+    )]
+    pub spaceship: Account<'info, SpaceShip>,
+}
+
+#[event]
+pub struct PowerupUpgraded {
+    pub realm_name: String,
+    pub user: Pubkey,
+    pub spaceship: SpaceShipLite,
+    pub powerup_type: PowerUpType,
+    pub index: u8,
+    pub tier: Tier,
+    pub price: u16,
+}
+
+pub fn upgrade_powerup(
+    ctx: Context<UpgradePowerup>,
+    powerup_type: PowerUpType,
+    index: u8,
+    duplicate_cargo_index: Option<u8>,
+) -> Result<()> {
+    // Validations
+    {
+        ctx.accounts.spaceship.ensure_fitting_unlocked()?;
+    }
+
+    // raise the tier and pay for it (built-in balance validation)
+    let (tier, price) = {
+        let spaceship = &mut ctx.accounts.spaceship;
+        // the copy is matched against the current tier, so it goes first
+        if let Some(cargo_index) = duplicate_cargo_index {
+            spaceship.consume_duplicate(powerup_type, index as usize, cargo_index as usize)?;
+        }
+        let (rarity, tier) = spaceship.upgrade(powerup_type, index as usize)?;
+        let price = upgrade_price(rarity, tier, duplicate_cargo_index.is_some());
+        spaceship.wallet.debit(price, UPGRADE_CURRENCY)?;
+        (tier, price)
+    };
+
+    emit!(PowerupUpgraded {
+        realm_name: ctx.accounts.realm.name.to_string(),
+        user: ctx.accounts.user.key(),
+        spaceship: SpaceShipLite::from_spaceship_account(&ctx.accounts.spaceship),
+        powerup_type,
+        index,
+        tier,
+        price,
+    });
+
+    Ok(())
+}
+
+pub fn upgrade_price(rarity: Rarity, tier: Tier, with_duplicate: bool) -> u16 {
+    let tier_price = match tier {
+        Tier::I => 0,
+        Tier::II => TIER_II_UPGRADE_PRICE,
+        Tier::III => TIER_III_UPGRADE_PRICE,
+    };
+    let rarity_multiplier = match rarity {
+        Rarity::Common => 1,
+        Rarity::Uncommon => 2,
+        Rarity::Rare => 3,
+        Rarity::Faction => 4,
+    };
+    let price = tier_price * rarity_multiplier;
+    // a copy of the power-up pays for half of the upgrade
+    if with_duplicate {
+        price / 2
+    } else {
+        price
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upgrade_price() {
+        assert_eq!(
+            upgrade_price(Rarity::Common, Tier::II, false),
+            TIER_II_UPGRADE_PRICE
+        );
+        assert_eq!(
+            upgrade_price(Rarity::Faction, Tier::III, false),
+            TIER_III_UPGRADE_PRICE * 4
+        );
+        // the duplicate discount
+        assert_eq!(
+            upgrade_price(Rarity::Uncommon, Tier::II, true),
+            upgrade_price(Rarity::Uncommon, Tier::II, false) / 2
+        );
+
+        // rarer power-ups cost more to upgrade
+        assert!(
+            upgrade_price(Rarity::Common, Tier::III, false)
+                > upgrade_price(Rarity::Common, Tier::II, false)
+        );
+        assert!(
+            upgrade_price(Rarity::Rare, Tier::II, false)
+                > upgrade_price(Rarity::Common, Tier::II, false)
+        );
+    }
+}
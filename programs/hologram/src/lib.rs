@@ -4,7 +4,11 @@ pub mod instructions;
 pub mod state;
 pub mod utils;
 
-use {anchor_lang::prelude::*, instructions::*};
+use {
+    anchor_lang::prelude::*,
+    instructions::*,
+    state::{CrateOdds, Gambit, LootEntry, PowerUpLocation, PowerUpType},
+};
 
 #[cfg(feature = "localnet")]
 declare_id!("GiN7xhFgwGTciboPZHyGu2v16LDezaXgkhMW9Pv5xiet");
@@ -33,14 +37,57 @@ pub const BASE_JAMMING_NULLIFYING_CHANCE: u8 = 10; // 10%
 pub const JAMMING_NULLIFYING_CHANCE_CAP: u8 = 75; // 75%
 pub const BASE_HULL_HITPOINTS: u8 = 30;
 pub const BASE_SHIELD_LAYERS: u8 = 0;
+pub const BASE_ARMOR_HITPOINTS: u8 = 0;
+// projectiles damages are multiplied against armor
+pub const PROJECTILE_ARMOR_DAMAGE_MULTIPLIER: u8 = 2;
 pub const ARENA_MATCHMAKING_FUEL_COST: u8 = 1;
 pub const ARENA_MATCHMAKING_ORDNANCE_PER_RANGE: u8 = 2;
 pub const ARENA_MATCHMAKING_SPACESHIPS_PER_RANGE: u8 = 5;
 pub const MATCH_MAX_TURN: u16 = 1000;
+// Stalled fights escalate: shields stop regenerating and hulls take attrition damage each turn
+pub const ESCALATION_START_TURN: u16 = 150;
+// attrition damage grows by 1 every ESCALATION_RAMP_INTERVAL turns
+pub const ESCALATION_RAMP_INTERVAL: u16 = 10;
 pub const CHARGE_PER_TURN: u8 = 1;
+// energy shared by the active powerups, each activation drains some
+pub const BASE_CAPACITOR_CAPACITY: u8 = 20;
+pub const BASE_CAPACITOR_RECHARGE_PER_TURN: u8 = 2;
+// energy drained per activation, weapons cost depends on their type (see WeaponType::energy_cost)
+pub const REPAIRER_ENERGY_COST: u8 = 3;
+pub const JAMMER_ENERGY_COST: u8 = 2;
+pub const DRONE_ENERGY_COST: u8 = 1;
 pub const HEAT_DISSIPATION_PER_TURN: u8 = 1;
 pub const BASE_HEAT: u8 = 5;
 pub const BASE_JAM_CHANCE: u8 = 100;
+pub const STATUS_EFFECT_MAX_STACKS: u8 = 3;
+
+// critical hits only come from powerups
+pub const BASE_CRITICAL_HIT_CHANCE: u8 = 0; // 0%
+pub const CRITICAL_HIT_CHANCE_CAP: u8 = 40; // 40%
+
+// damage multiplier of critical hits, in percent
+pub const BASE_CRITICAL_HIT_MULTIPLIER: u8 = 150; // x1.5
+pub const CRITICAL_HIT_MULTIPLIER_CAP: u8 = 250; // x2.5
+
+// effects of the spaceship with the highest initiative resolve first each turn
+pub const BASE_INITIATIVE: u8 = 10;
+pub const INITIATIVE_CAP: u8 = 50;
+
+// arena environments, in percent (deep space gets the rest)
+pub const ION_STORM_CHANCE: u8 = 15;
+pub const NEBULA_CHANCE: u8 = 15;
+pub const ASTEROID_FIELD_CHANCE: u8 = 15;
+pub const SOLAR_FLARE_CHANCE: u8 = 15;
+pub const NEBULA_DODGE_CHANCE_BONUS: u8 = 10; // 10%
+pub const ASTEROID_FIELD_MISSILE_DAMAGE_MALUS: u8 = 1;
+pub const SOLAR_FLARE_LASER_DAMAGE_BONUS: u8 = 1;
+
+// how many activation rules a spaceship can store
+pub const MAX_GAMBITS: usize = 8;
+// weight of the loot table entries the realm loot table is initialized with
+pub const DEFAULT_LOOT_ENTRY_WEIGHT: u8 = 10;
+// entries are addressed with a u8 index
+pub const MAX_LOOT_TABLE_ENTRIES: usize = 128;
 
 solana_security_txt::security_txt! {
     name: "Hologram",
@@ -65,6 +112,40 @@ pub mod hologram {
         instructions::initialize_realm(ctx, name)
     }
 
+    // Admin IX ------------------------------------------------------------------
+
+    // Create the realm loot table, filled with the default loot tables and crate odds.
+    // The crates of the realm can't be settled before it exists.
+    pub fn initialize_loot_table(ctx: Context<InitializeLootTable>) -> Result<()> {
+        instructions::initialize_loot_table(ctx)
+    }
+
+    // Add a module, drone or mutation to what the crates can drop
+    pub fn add_loot_entry(ctx: Context<AddLootEntry>, entry: LootEntry) -> Result<()> {
+        instructions::add_loot_entry(ctx, entry)
+    }
+
+    // Remove an entry from the loot table, as long as every rarity a crate can roll keeps one
+    pub fn remove_loot_entry(ctx: Context<RemoveLootEntry>, index: u8) -> Result<()> {
+        instructions::remove_loot_entry(ctx, index)
+    }
+
+    // Change how likely an entry is to drop within its rarity
+    pub fn set_loot_entry_weight(
+        ctx: Context<SetLootEntryWeight>,
+        index: u8,
+        weight: u8,
+    ) -> Result<()> {
+        instructions::set_loot_entry_weight(ctx, index, weight)
+    }
+
+    // Change the outcome odds of a crate type
+    pub fn set_crate_odds(ctx: Context<SetCrateOdds>, crate_odds: CrateOdds) -> Result<()> {
+        instructions::set_crate_odds(ctx, crate_odds)
+    }
+
+    // Player IX ------------------------------------------------------------------
+
     // Create a user_account tied to a realm, this will store a player information and spaceships
     pub fn create_user_account(ctx: Context<CreateUserAccount>) -> Result<()> {
         instructions::create_user_account(ctx)
@@ -133,4 +214,57 @@ pub mod hologram {
     ) -> Result<()> {
         instructions::pick_crate_settle(ctx, generated_seed, crate_type)
     }
+
+    // Fit a power-up stored in the cargo hold
+    #[allow(unused_variables)]
+    pub fn equip_powerup(
+        ctx: Context<EquipPowerup>,
+        spaceship_index: u8,
+        cargo_index: u8,
+    ) -> Result<()> {
+        instructions::equip_powerup(ctx, cargo_index)
+    }
+
+    // Move a fitted power-up to the cargo hold
+    #[allow(unused_variables)]
+    pub fn unequip_powerup(
+        ctx: Context<UnequipPowerup>,
+        spaceship_index: u8,
+        powerup_type: PowerUpType,
+        index: u8,
+    ) -> Result<()> {
+        instructions::unequip_powerup(ctx, powerup_type, index)
+    }
+
+    // Raise the tier of a fitted module or drone, paying with currency (and optionally a copy of it)
+    #[allow(unused_variables)]
+    pub fn upgrade_powerup(
+        ctx: Context<UpgradePowerup>,
+        spaceship_index: u8,
+        powerup_type: PowerUpType,
+        index: u8,
+        duplicate_cargo_index: Option<u8>,
+    ) -> Result<()> {
+        instructions::upgrade_powerup(ctx, powerup_type, index, duplicate_cargo_index)
+    }
+
+    // Scrap a fitted or stored power-up for currency, freeing its ordnance
+    #[allow(unused_variables)]
+    pub fn salvage_powerup(
+        ctx: Context<SalvagePowerup>,
+        spaceship_index: u8,
+        location: PowerUpLocation,
+    ) -> Result<()> {
+        instructions::salvage_powerup(ctx, location)
+    }
+
+    // Replace the activation rules of the spaceship active power-ups
+    #[allow(unused_variables)]
+    pub fn set_gambits(
+        ctx: Context<SetGambits>,
+        spaceship_index: u8,
+        gambits: Vec<Gambit>,
+    ) -> Result<()> {
+        instructions::set_gambits(ctx, gambits)
+    }
 }
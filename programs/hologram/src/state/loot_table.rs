@@ -0,0 +1,449 @@
+use {
+    super::{Drone, Module, Mutation, Rarity},
+    crate::{
+        engine::{
+            LT_DRONE_COMMON, LT_DRONE_OFFENSIVE_FACTION, LT_DRONE_OFFENSIVE_RARE,
+            LT_DRONE_OFFENSIVE_UNCOMMON, LT_MODULES_COMMON, LT_MODULES_FACTION, LT_MODULES_RARE,
+            LT_MODULES_UNCOMMON, LT_MUTATIONS_RARE, LT_MUTATIONS_UNCOMMON,
+        },
+        error::HologramError,
+        instructions::{
+            CrateType, BMC_DRONE_CHANCE, BMC_FACTION_RARITY_ENABLED, BMC_MODULE_CHANCE,
+            BMC_MUTATION_CHANCE, BMC_PITY_RARITY_BONUS, BMC_PITY_THRESHOLD, BMC_SCAM_CHANCE,
+            NI_DRONE_CHANCE, NI_FACTION_RARITY_ENABLED, NI_MODULE_CHANCE, NI_MUTATION_CHANCE,
+            NI_PITY_RARITY_BONUS, NI_PITY_THRESHOLD, NI_SCAM_CHANCE, PC_DRONE_CHANCE,
+            PC_FACTION_RARITY_ENABLED, PC_MODULE_CHANCE, PC_MUTATION_CHANCE, PC_PITY_RARITY_BONUS,
+            PC_PITY_THRESHOLD, PC_SCAM_CHANCE,
+        },
+        utils::LimitedString,
+        DEFAULT_LOOT_ENTRY_WEIGHT, MAX_LOOT_TABLE_ENTRIES,
+    },
+    anchor_lang::prelude::*,
+};
+
+// What the crates of a realm can drop, editable by the realm admin without redeploying the program
+// Note: the LT_* consts and the crate chances consts are the defaults the table is initialized with
+#[account()]
+pub struct LootTable {
+    pub bump: u8,
+    pub realm: Pubkey,
+    pub entries: Vec<LootEntry>,
+    // one for each CrateType
+    pub crate_odds: Vec<CrateOdds>,
+}
+
+impl LootTable {
+    pub const LEN: usize = 8 + std::mem::size_of::<LootTable>();
+    // size of the default loot tables, see LootTable::default
+    pub const DEFAULT_ENTRIES: usize = LT_MODULES_COMMON.len()
+        + LT_MODULES_UNCOMMON.len()
+        + LT_MODULES_RARE.len()
+        + LT_MODULES_FACTION.len()
+        + LT_DRONE_COMMON.len()
+        + LT_DRONE_OFFENSIVE_UNCOMMON.len()
+        + LT_DRONE_OFFENSIVE_RARE.len()
+        + LT_DRONE_OFFENSIVE_FACTION.len()
+        + LT_MUTATIONS_UNCOMMON.len()
+        + LT_MUTATIONS_RARE.len();
+    pub const DEFAULT_CRATE_ODDS: usize = 3;
+
+    pub fn space(entries: usize, crate_odds: usize) -> usize {
+        LootTable::LEN
+            + std::mem::size_of::<LootEntry>() * entries
+            + std::mem::size_of::<CrateOdds>() * crate_odds
+    }
+}
+
+// Within a rarity bucket, each entry drops proportionally to its weight
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct LootEntry {
+    pub weight: u8,
+    pub item: LootItem,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub enum LootItem {
+    Module(Module),
+    Drone(Drone),
+    Mutation(Mutation),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum LootKind {
+    Module,
+    Drone,
+    Mutation,
+}
+
+impl LootItem {
+    pub fn kind(&self) -> LootKind {
+        match self {
+            LootItem::Module(_) => LootKind::Module,
+            LootItem::Drone(_) => LootKind::Drone,
+            LootItem::Mutation(_) => LootKind::Mutation,
+        }
+    }
+
+    pub fn name(&self) -> LimitedString {
+        match self {
+            LootItem::Module(module) => module.name,
+            LootItem::Drone(drone) => drone.name,
+            LootItem::Mutation(mutation) => mutation.name,
+        }
+    }
+
+    pub fn rarity(&self) -> Rarity {
+        match self {
+            LootItem::Module(module) => module.rarity,
+            LootItem::Drone(drone) => drone.rarity,
+            LootItem::Mutation(mutation) => mutation.rarity,
+        }
+    }
+}
+
+// Odds of each outcome of a crate, the weights must total 100
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub struct CrateOdds {
+    pub crate_type: CrateType,
+    pub module_weight: u8,
+    pub drone_weight: u8,
+    pub mutation_weight: u8,
+    pub scam_weight: u8,
+    pub faction_rarity_enabled: bool,
+    // sub-Rare drops in a row before the pity kicks in, 0 disables it
+    pub pity_threshold: u8,
+    // Rare/Faction chance added for each drop past the threshold
+    pub pity_rarity_bonus: u8,
+}
+
+impl CrateOdds {
+    pub fn total_weight(&self) -> u16 {
+        self.module_weight as u16
+            + self.drone_weight as u16
+            + self.mutation_weight as u16
+            + self.scam_weight as u16
+    }
+
+    // Rare/Faction chance bonus for a spaceship that didn't see a Rare in `pity_counter` drops
+    pub fn pity_bonus(&self, pity_counter: u8) -> u8 {
+        if self.pity_threshold == 0 || pity_counter < self.pity_threshold {
+            return 0;
+        }
+        (pity_counter - self.pity_threshold + 1).saturating_mul(self.pity_rarity_bonus)
+    }
+}
+
+impl Default for LootTable {
+    fn default() -> Self {
+        // Note: iterate over references, the tables are too large to be copied on the stack
+        let modules = LT_MODULES_COMMON
+            .iter()
+            .chain(LT_MODULES_UNCOMMON.iter())
+            .chain(LT_MODULES_RARE.iter())
+            .chain(LT_MODULES_FACTION.iter())
+            .cloned()
+            .map(LootItem::Module);
+        let drones = LT_DRONE_COMMON
+            .iter()
+            .chain(LT_DRONE_OFFENSIVE_UNCOMMON.iter())
+            .chain(LT_DRONE_OFFENSIVE_RARE.iter())
+            .chain(LT_DRONE_OFFENSIVE_FACTION.iter())
+            .cloned()
+            .map(LootItem::Drone);
+        let mutations = LT_MUTATIONS_UNCOMMON
+            .iter()
+            .chain(LT_MUTATIONS_RARE.iter())
+            .cloned()
+            .map(LootItem::Mutation);
+        Self {
+            bump: 0,
+            realm: Pubkey::default(),
+            entries: modules
+                .chain(drones)
+                .chain(mutations)
+                .map(|item| LootEntry {
+                    weight: DEFAULT_LOOT_ENTRY_WEIGHT,
+                    item,
+                })
+                .collect(),
+            crate_odds: vec![
+                CrateOdds {
+                    crate_type: CrateType::NavyIssue,
+                    module_weight: NI_MODULE_CHANCE,
+                    drone_weight: NI_DRONE_CHANCE,
+                    mutation_weight: NI_MUTATION_CHANCE,
+                    scam_weight: NI_SCAM_CHANCE,
+                    faction_rarity_enabled: NI_FACTION_RARITY_ENABLED,
+                    pity_threshold: NI_PITY_THRESHOLD,
+                    pity_rarity_bonus: NI_PITY_RARITY_BONUS,
+                },
+                CrateOdds {
+                    crate_type: CrateType::PirateContraband,
+                    module_weight: PC_MODULE_CHANCE,
+                    drone_weight: PC_DRONE_CHANCE,
+                    mutation_weight: PC_MUTATION_CHANCE,
+                    scam_weight: PC_SCAM_CHANCE,
+                    faction_rarity_enabled: PC_FACTION_RARITY_ENABLED,
+                    pity_threshold: PC_PITY_THRESHOLD,
+                    pity_rarity_bonus: PC_PITY_RARITY_BONUS,
+                },
+                CrateOdds {
+                    crate_type: CrateType::BiomechanicalCache,
+                    module_weight: BMC_MODULE_CHANCE,
+                    drone_weight: BMC_DRONE_CHANCE,
+                    mutation_weight: BMC_MUTATION_CHANCE,
+                    scam_weight: BMC_SCAM_CHANCE,
+                    faction_rarity_enabled: BMC_FACTION_RARITY_ENABLED,
+                    pity_threshold: BMC_PITY_THRESHOLD,
+                    pity_rarity_bonus: BMC_PITY_RARITY_BONUS,
+                },
+            ],
+        }
+    }
+}
+
+impl LootTable {
+    pub fn get_crate_odds(&self, crate_type: CrateType) -> Result<&CrateOdds> {
+        self.crate_odds
+            .iter()
+            .find(|o| o.crate_type == crate_type)
+            .ok_or(error!(HologramError::InvalidCrateOdds))
+    }
+
+    // entries of a kind and rarity, the LootEngine rolls among them
+    pub fn bucket(&self, kind: LootKind, rarity: Rarity) -> Vec<&LootEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.item.kind() == kind && e.item.rarity() == rarity)
+            .collect()
+    }
+
+    pub fn add_entry(&mut self, entry: LootEntry) -> Result<()> {
+        require!(
+            self.entries.len() < MAX_LOOT_TABLE_ENTRIES,
+            HologramError::InvalidLootEntry
+        );
+        require!(entry.weight > 0, HologramError::InvalidLootEntry);
+        // mutations only drop as Uncommon or Rare, see LootEngine::drop_mutation
+        require!(
+            entry.item.kind() != LootKind::Mutation
+                || matches!(entry.item.rarity(), Rarity::Uncommon | Rarity::Rare),
+            HologramError::InvalidLootEntry
+        );
+        require!(
+            !self
+                .entries
+                .iter()
+                .any(|e| e.item.kind() == entry.item.kind() && e.item.name() == entry.item.name()),
+            HologramError::InvalidLootEntry
+        );
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    pub fn remove_entry(&mut self, index: u8) -> Result<LootEntry> {
+        require!(
+            (index as usize) < self.entries.len(),
+            HologramError::InvalidLootEntry
+        );
+        let entry = self.entries.remove(index as usize);
+        // put it back, the table must stay valid
+        if let Err(error) = self.validate() {
+            self.entries.insert(index as usize, entry);
+            return Err(error);
+        }
+        Ok(entry)
+    }
+
+    pub fn set_entry_weight(&mut self, index: u8, weight: u8) -> Result<()> {
+        require!(weight > 0, HologramError::InvalidLootEntry);
+        let entry = self
+            .entries
+            .get_mut(index as usize)
+            .ok_or(error!(HologramError::InvalidLootEntry))?;
+        entry.weight = weight;
+        Ok(())
+    }
+
+    pub fn set_crate_odds(&mut self, crate_odds: CrateOdds) -> Result<()> {
+        require!(
+            crate_odds.total_weight() == 100,
+            HologramError::InvalidCrateOdds
+        );
+        let odds = self
+            .crate_odds
+            .iter_mut()
+            .find(|o| o.crate_type == crate_odds.crate_type)
+            .ok_or(error!(HologramError::InvalidCrateOdds))?;
+        *odds = crate_odds;
+        self.validate()
+    }
+
+    // Every rarity bucket a crate can roll must hold at least one entry
+    pub fn validate(&self) -> Result<()> {
+        for odds in self.crate_odds.iter() {
+            require!(odds.total_weight() == 100, HologramError::InvalidCrateOdds);
+            let mut rarities = vec![Rarity::Common, Rarity::Uncommon, Rarity::Rare];
+            if odds.faction_rarity_enabled {
+                rarities.push(Rarity::Faction);
+            }
+            let mut required_buckets = vec![];
+            if odds.module_weight > 0 {
+                required_buckets.extend(rarities.iter().map(|r| (LootKind::Module, *r)));
+            }
+            if odds.drone_weight > 0 {
+                required_buckets.extend(rarities.iter().map(|r| (LootKind::Drone, *r)));
+            }
+            if odds.mutation_weight > 0 {
+                required_buckets.push((LootKind::Mutation, Rarity::Uncommon));
+                required_buckets.push((LootKind::Mutation, Rarity::Rare));
+            }
+            for (kind, rarity) in required_buckets {
+                require!(
+                    !self.bucket(kind, rarity).is_empty(),
+                    HologramError::InvalidLootTable
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_entry(loot_table: &LootTable, kind: LootKind) -> LootEntry {
+        loot_table
+            .entries
+            .iter()
+            .find(|e| e.item.kind() == kind)
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_default_loot_table() {
+        let loot_table = LootTable::default();
+        assert_eq!(loot_table.entries.len(), LootTable::DEFAULT_ENTRIES);
+        assert_eq!(loot_table.crate_odds.len(), LootTable::DEFAULT_CRATE_ODDS);
+        assert!(loot_table.validate().is_ok());
+        // initialize_loot_table creates the account through a CPI, capped at 10KB
+        assert!(
+            LootTable::space(LootTable::DEFAULT_ENTRIES, LootTable::DEFAULT_CRATE_ODDS)
+                <= anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE
+        );
+    }
+
+    #[test]
+    fn test_crate_odds_total_100() {
+        let mut loot_table = LootTable::default();
+        let mut odds = *loot_table.get_crate_odds(CrateType::NavyIssue).unwrap();
+        odds.scam_weight += 1;
+        assert!(loot_table.set_crate_odds(odds).is_err());
+        odds.module_weight -= 1;
+        assert!(loot_table.set_crate_odds(odds).is_ok());
+        assert_eq!(
+            loot_table
+                .get_crate_odds(CrateType::NavyIssue)
+                .unwrap()
+                .scam_weight,
+            odds.scam_weight
+        );
+    }
+
+    #[test]
+    fn test_entry_weight_above_zero() {
+        let mut loot_table = LootTable::default();
+        assert!(loot_table.set_entry_weight(0, 0).is_err());
+        assert!(loot_table.set_entry_weight(0, u8::MAX).is_ok());
+        assert!(loot_table
+            .set_entry_weight(loot_table.entries.len() as u8, 1)
+            .is_err());
+
+        let mut module = first_entry(&loot_table, LootKind::Module);
+        if let LootItem::Module(m) = &mut module.item {
+            m.name = LimitedString::new("Salvaged Autocannon");
+        }
+        module.weight = 0;
+        assert!(loot_table.add_entry(module.clone()).is_err());
+        module.weight = 1;
+        assert!(loot_table.add_entry(module).is_ok());
+    }
+
+    #[test]
+    fn test_add_entry() {
+        let mut loot_table = LootTable::default();
+
+        // no duplicates, and mutations only drop as Uncommon or Rare
+        let mutation = first_entry(&loot_table, LootKind::Mutation);
+        assert!(loot_table.add_entry(mutation.clone()).is_err());
+        let mut common_mutation = mutation;
+        if let LootItem::Mutation(m) = &mut common_mutation.item {
+            m.name = LimitedString::new("Vestigial Organ");
+            m.rarity = Rarity::Common;
+        }
+        assert!(loot_table.add_entry(common_mutation).is_err());
+
+        // up to MAX_LOOT_TABLE_ENTRIES
+        let filler = first_entry(&loot_table, LootKind::Drone);
+        loot_table.entries.resize(MAX_LOOT_TABLE_ENTRIES, filler);
+        let mut module = first_entry(&loot_table, LootKind::Module);
+        if let LootItem::Module(m) = &mut module.item {
+            m.name = LimitedString::new("Salvaged Autocannon");
+        }
+        assert!(loot_table.add_entry(module.clone()).is_err());
+        loot_table.entries.pop();
+        assert!(loot_table.add_entry(module).is_ok());
+        assert_eq!(loot_table.entries.len(), MAX_LOOT_TABLE_ENTRIES);
+    }
+
+    #[test]
+    fn test_remove_entry_keeps_table_valid() {
+        let rare_mutations = |loot_table: &LootTable| {
+            loot_table
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, e)| {
+                    e.item.kind() == LootKind::Mutation && e.item.rarity() == Rarity::Rare
+                })
+                .map(|(i, _)| i as u8)
+                .collect::<Vec<_>>()
+        };
+        let mut loot_table = LootTable::default();
+        let indexes = rare_mutations(&loot_table);
+        let last = indexes.len() - 1;
+        for i in indexes.into_iter().take(last).rev() {
+            loot_table.remove_entry(i).unwrap();
+        }
+        // a crate can't roll an empty rarity bucket
+        let entries = loot_table.entries.len();
+        assert!(loot_table
+            .remove_entry(rare_mutations(&loot_table)[0])
+            .is_err());
+        assert_eq!(loot_table.entries.len(), entries);
+        assert!(loot_table.validate().is_ok());
+    }
+
+    #[test]
+    fn test_pity_bonus() {
+        let loot_table = LootTable::default();
+        let odds = *loot_table.get_crate_odds(CrateType::NavyIssue).unwrap();
+        assert_eq!(odds.pity_bonus(0), 0);
+        assert_eq!(odds.pity_bonus(odds.pity_threshold - 1), 0);
+        assert_eq!(odds.pity_bonus(odds.pity_threshold), odds.pity_rarity_bonus);
+        assert_eq!(
+            odds.pity_bonus(odds.pity_threshold + 2),
+            3 * odds.pity_rarity_bonus
+        );
+        assert_eq!(odds.pity_bonus(u8::MAX), u8::MAX);
+
+        // a threshold of 0 disables the pity
+        let disabled = CrateOdds {
+            pity_threshold: 0,
+            ..odds
+        };
+        assert_eq!(disabled.pity_bonus(u8::MAX), 0);
+    }
+}
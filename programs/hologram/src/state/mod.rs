@@ -1,7 +1,8 @@
+pub mod loot_table;
 pub mod realm;
 pub mod spaceship;
 pub mod sub_types;
 pub mod user_account;
 
 // bring everything in scope
-pub use {realm::*, spaceship::*, sub_types::*, user_account::*};
+pub use {loot_table::*, realm::*, spaceship::*, sub_types::*, user_account::*};
@@ -1,9 +1,13 @@
 use {
     super::{Fuel, SwitchboardFunctionRequestStatus, SwitchboardRequestInfo, Wallet},
     crate::{
+        engine::{Condition, Effect, PowerUp},
         error::HologramError,
+        instructions::CrateType,
         utils::{LimitedString, RandomNumberGenerator},
-        FUEL_ALLOWANCE_AMOUNT, FUEL_ALLOWANCE_COOLDOWN, MAX_ORDNANCE,
+        BASE_DODGE_CHANCE, BASE_HULL_HITPOINTS, BASE_INITIATIVE, BASE_JAMMING_NULLIFYING_CHANCE,
+        BASE_SHIELD_LAYERS, FUEL_ALLOWANCE_AMOUNT, FUEL_ALLOWANCE_COOLDOWN, MAX_GAMBITS,
+        MAX_ORDNANCE,
     },
     anchor_lang::prelude::*,
 };
@@ -28,6 +32,10 @@ pub struct SpaceShip {
     pub modules: Vec<Module>,
     pub drones: Vec<Drone>,
     pub mutations: Vec<Mutation>,
+    // power-ups owned but not fitted, they don't take part in fights
+    pub cargo_hold: Vec<CargoItem>,
+    // player defined activation rules for the active power-ups
+    pub gambits: Vec<Gambit>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -50,6 +58,141 @@ pub enum Hull {
     FactionOne,
 }
 
+impl Hull {
+    // every hull has the same odds
+    pub fn roll(rng: &mut RandomNumberGenerator) -> Self {
+        let dice_roll = rng.roll_dice(10); // waiting for mem::variant_count::<Hull>() to be non nightly only rust...
+        match dice_roll {
+            1 => Hull::CommonOne,
+            2 => Hull::CommonTwo,
+            3 => Hull::CommonThree,
+            4 => Hull::UncommonOne,
+            5 => Hull::UncommonTwo,
+            6 => Hull::UncommonThree,
+            7 => Hull::UncommonFour,
+            8 => Hull::RareOne,
+            9 => Hull::RareTwo,
+            10 => Hull::FactionOne,
+            _ => panic!("Invalid dice roll"),
+        }
+    }
+
+    // base stats of the spaceship, before any powerup
+    pub fn profile(&self) -> HullProfile {
+        let (hull_hitpoints, shield_layers, dodge_chance, jamming_nullifying_chance, initiative) =
+            match self {
+                Hull::CommonOne => (
+                    BASE_HULL_HITPOINTS,
+                    BASE_SHIELD_LAYERS,
+                    BASE_DODGE_CHANCE,
+                    BASE_JAMMING_NULLIFYING_CHANCE,
+                    BASE_INITIATIVE,
+                ),
+                Hull::CommonTwo => (34, 0, 3, 10, 8),
+                Hull::CommonThree => (26, 0, 9, 10, 13),
+                Hull::UncommonOne => (32, 1, 5, 10, 10),
+                Hull::UncommonTwo => (36, 0, 4, 15, 8),
+                Hull::UncommonThree => (28, 0, 12, 10, 14),
+                Hull::UncommonFour => (30, 1, 7, 15, 11),
+                Hull::RareOne => (38, 1, 5, 15, 9),
+                Hull::RareTwo => (34, 2, 10, 20, 13),
+                Hull::FactionOne => (40, 2, 8, 20, 12),
+            };
+        HullProfile {
+            hull_hitpoints,
+            shield_layers,
+            dodge_chance,
+            jamming_nullifying_chance,
+            initiative,
+        }
+    }
+
+    pub fn slot_layout(&self) -> SlotLayout {
+        let (weapon_slots, utility_slots, drone_bay, power_grid) = match self {
+            Hull::CommonOne => (3, 3, 8, 10),
+            Hull::CommonTwo => (3, 4, 6, 9),
+            Hull::CommonThree => (4, 2, 8, 10),
+            Hull::UncommonOne => (3, 4, 8, 11),
+            Hull::UncommonTwo => (4, 3, 6, 11),
+            Hull::UncommonThree => (3, 3, 10, 10),
+            Hull::UncommonFour => (4, 4, 8, 12),
+            Hull::RareOne => (4, 5, 8, 13),
+            Hull::RareTwo => (5, 4, 10, 14),
+            Hull::FactionOne => (5, 5, 10, 15),
+        };
+        SlotLayout {
+            weapon_slots,
+            utility_slots,
+            drone_bay,
+            power_grid,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub struct HullProfile {
+    pub hull_hitpoints: u8,
+    pub shield_layers: u8,
+    pub dodge_chance: u8,
+    pub jamming_nullifying_chance: u8,
+    // effects of faster spaceships resolve first each turn
+    pub initiative: u8,
+}
+
+// How much the hull can carry
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub struct SlotLayout {
+    pub weapon_slots: u8,
+    pub utility_slots: u8,
+    // room for drones, see DroneSize::bay_usage
+    pub drone_bay: u8,
+    // power available to the fitted modules, see Module::power_usage
+    pub power_grid: u8,
+}
+
+// The kind of slot a module is fitted in
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Slot {
+    Weapon,
+    Utility,
+}
+
+// A power-up sitting in the cargo hold
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub enum CargoItem {
+    Module(Module),
+    Drone(Drone),
+    Mutation(Mutation),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PowerUpType {
+    Module,
+    Drone,
+    Mutation,
+}
+
+// Where an owned power-up sits on the spaceship
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PowerUpLocation {
+    Fitted {
+        powerup_type: PowerUpType,
+        index: u8,
+    },
+    CargoHold {
+        cargo_index: u8,
+    },
+}
+
+// Hold a fitted active power-up once charged, until the condition is met
+// The condition is evaluated against the opponents, it's met if it holds for one of them
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub struct Gambit {
+    pub powerup_type: PowerUpType,
+    pub index: u8,
+    pub condition: Condition,
+}
+
 // Randomness is initially seeded using a Switchboard Function (custom).
 // The function is called once only. Randomness is then iterated over using Xorshift.
 // This is initially used for the Hull skin roll at spaceship creation, but it's available as an interative RNG for other use cases.
@@ -90,6 +233,23 @@ pub struct ArenaMatchmaking {
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct CratePicking {
     pub switchboard_request_info: SwitchboardRequestInfo,
+    // sub-Rare drops in a row, one for each CrateType, see CrateOdds::pity_bonus
+    pub pity_counters: [u8; 3],
+}
+
+impl CratePicking {
+    pub fn pity_counter(&self, crate_type: CrateType) -> u8 {
+        self.pity_counters[crate_type as usize]
+    }
+
+    // a Rare or Faction drop resets the counter of the crate type, anything else increases it
+    pub fn record_drop(&mut self, crate_type: CrateType, rarity: Rarity) {
+        let counter = &mut self.pity_counters[crate_type as usize];
+        *counter = match rarity {
+            Rarity::Rare | Rarity::Faction => 0,
+            Rarity::Common | Rarity::Uncommon => counter.saturating_add(1),
+        };
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -109,6 +269,34 @@ pub struct Module {
     pub rarity: Rarity,
     pub class: ModuleClass,
     pub is_active: bool,
+    pub tier: Tier,
+}
+
+impl Module {
+    pub fn slot(&self) -> Slot {
+        match &self.class {
+            ModuleClass::Weapon(_) | ModuleClass::AntiDrone(_) => Slot::Weapon,
+            ModuleClass::Repairer(_, _)
+            | ModuleClass::Capacitative(_, _)
+            | ModuleClass::Jammer(_, _)
+            | ModuleClass::Disruptor(_)
+            | ModuleClass::Reactive(_, _)
+            | ModuleClass::PointDefense(_, _)
+            | ModuleClass::Capacitor(_, _)
+            | ModuleClass::Upgrade(_)
+            | ModuleClass::Afterburner(_, _) => Slot::Utility,
+        }
+    }
+
+    // how much of the hull power grid the module draws once fitted
+    pub fn power_usage(&self) -> u8 {
+        match self.rarity {
+            Rarity::Common => 1,
+            Rarity::Uncommon => 2,
+            Rarity::Rare => 3,
+            Rarity::Faction => 4,
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -119,6 +307,7 @@ pub struct Drone {
     pub size: DroneSize,
     pub class: DroneClass,
     pub is_active: bool,
+    pub tier: Tier,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -126,12 +315,82 @@ pub struct Mutation {
     pub name: LimitedString,
     // pub description: LongLimitedString,
     pub rarity: Rarity,
+    pub class: MutationClass,
     pub is_active: bool,
+    // a rarer mutation of the family evolves the owned one instead of stacking with it
+    pub family: Option<MutationFamily>,
 }
 
 impl SpaceShip {
+    // A settled spaceship with nothing fitted that isn't backed by an account (off-chain tools)
+    pub fn new_offchain(name: LimitedString, hull: Hull) -> Self {
+        SpaceShip {
+            bump: 0,
+            owner: Pubkey::default(),
+            id: 0,
+            name,
+            analytics: SpaceShipAnalytics {
+                total_arena_matches: 0,
+                total_arena_victories: 0,
+            },
+            randomness: Randomness {
+                switchboard_request_info: SwitchboardRequestInfo {
+                    account: Pubkey::default(),
+                    status: SwitchboardFunctionRequestStatus::Settled { slot: 0 },
+                },
+                original_seed: 0,
+                current_seed: 0,
+                iteration: 0,
+            },
+            arena_matchmaking: ArenaMatchmaking {
+                switchboard_request_info: SwitchboardRequestInfo {
+                    account: Pubkey::default(),
+                    status: SwitchboardFunctionRequestStatus::Settled { slot: 0 },
+                },
+                matchmaking_status: MatchMakingStatus::None,
+            },
+            crate_picking: CratePicking {
+                switchboard_request_info: SwitchboardRequestInfo {
+                    account: Pubkey::default(),
+                    status: SwitchboardFunctionRequestStatus::Settled { slot: 0 },
+                },
+                pity_counters: [0; 3],
+            },
+            hull,
+            fuel: Fuel {
+                max: 0,
+                current: 0,
+                daily_allowance_last_collection: 0,
+            },
+            wallet: Wallet {
+                imperial_credits: 0,
+                activate_nanite_paste: 0,
+            },
+            modules: vec![],
+            drones: vec![],
+            mutations: vec![],
+            cargo_hold: vec![],
+            gambits: vec![],
+        }
+    }
+
     pub const LEN: usize = 8 + std::mem::size_of::<SpaceShip>();
 
+    pub fn space(
+        modules: usize,
+        drones: usize,
+        mutations: usize,
+        cargo_items: usize,
+        gambits: usize,
+    ) -> usize {
+        SpaceShip::LEN
+            + std::mem::size_of::<Module>() * modules
+            + std::mem::size_of::<Drone>() * drones
+            + std::mem::size_of::<Mutation>() * mutations
+            + std::mem::size_of::<CargoItem>() * cargo_items
+            + std::mem::size_of::<Gambit>() * gambits
+    }
+
     // the account has finished the initialization process
     pub fn is_initialized(&self) -> bool {
         matches!(
@@ -141,10 +400,40 @@ impl SpaceShip {
     }
 
     // the ordnance of the spaceship, a score loosely representing it's power
+    // (only fitted power-ups count, the cargo hold doesn't fight)
     pub fn ordnance(&self) -> u8 {
         self.modules.len() as u8 + self.drones.len() as u8 + self.mutations.len() as u8
     }
 
+    // every power-up the spaceship owns, fitted or in the cargo hold
+    pub fn owned_powerups(&self) -> u8 {
+        self.ordnance() + self.cargo_hold.len() as u8
+    }
+
+    // the fitting is locked while the spaceship is in the arena, it defined the matchmaking queue (and the spaceship
+    // fights as it was when it entered), and while a crate is picked, the settle relies on the space reserved in pick_crate
+    pub fn ensure_fitting_unlocked(&self) -> Result<()> {
+        require!(
+            !self
+                .arena_matchmaking
+                .switchboard_request_info
+                .is_requested(),
+            HologramError::ArenaMatchmakingAlreadyRequested
+        );
+        require!(
+            matches!(
+                self.arena_matchmaking.matchmaking_status,
+                MatchMakingStatus::None
+            ),
+            HologramError::ArenaMatchmakingAlreadyInQueue
+        );
+        require!(
+            !self.crate_picking.switchboard_request_info.is_requested(),
+            HologramError::CratePickingAlreadyRequested
+        );
+        Ok(())
+    }
+
     pub fn fuel_allowance_is_available(&self, current_time: i64) -> Result<bool> {
         let cooldown = current_time
             .checked_sub(FUEL_ALLOWANCE_COOLDOWN)
@@ -159,38 +448,282 @@ impl SpaceShip {
         Ok(())
     }
 
+    // slots of the given kind taken by the fitted modules
+    pub fn slot_usage(&self, slot: Slot) -> u8 {
+        self.modules.iter().filter(|m| m.slot() == slot).count() as u8
+    }
+
+    // power drawn by the fitted modules
+    pub fn power_usage(&self) -> u8 {
+        self.modules.iter().map(|m| m.power_usage()).sum()
+    }
+
+    pub fn can_fit_module(&self, module: &Module) -> bool {
+        let slot_layout = self.hull.slot_layout();
+        let slots = match module.slot() {
+            Slot::Weapon => slot_layout.weapon_slots,
+            Slot::Utility => slot_layout.utility_slots,
+        };
+        self.slot_usage(module.slot()) < slots
+            && self.power_usage() + module.power_usage() <= slot_layout.power_grid
+    }
+
+    // fit the module, or store it in the cargo hold when there is no room for it
     pub fn mount_module(&mut self, module: Module) -> Result<()> {
         require!(
-            self.ordnance() < MAX_ORDNANCE,
+            self.owned_powerups() < MAX_ORDNANCE,
             HologramError::MaxOrdnanceReached
         );
-        msg!("Module mounted: {:?}", module);
-        self.modules.push(module);
+        if self.can_fit_module(&module) {
+            msg!("Module mounted: {:?}", module);
+            self.modules.push(module);
+        } else {
+            msg!("No room to fit the module, stored in cargo: {:?}", module);
+            self.cargo_hold.push(CargoItem::Module(module));
+        }
         Ok(())
     }
 
+    // room taken by the drones in the drone bay
+    pub fn drone_bay_usage(&self) -> u8 {
+        self.drones.iter().map(|d| d.size.bay_usage()).sum()
+    }
+
+    pub fn can_load_drone(&self, drone: &Drone) -> bool {
+        self.drone_bay_usage() + drone.size.bay_usage() <= self.hull.slot_layout().drone_bay
+    }
+
+    // load the drone, or store it in the cargo hold when the drone bay is full
     pub fn load_drone(&mut self, drone: Drone) -> Result<()> {
         require!(
-            self.ordnance() < MAX_ORDNANCE,
+            self.owned_powerups() < MAX_ORDNANCE,
             HologramError::MaxOrdnanceReached
         );
-        msg!("Drone loaded: {:?}", drone);
-        self.drones.push(drone);
+        if self.can_load_drone(&drone) {
+            msg!("Drone loaded: {:?}", drone);
+            self.drones.push(drone);
+        } else {
+            msg!("No room in the drone bay, stored in cargo: {:?}", drone);
+            self.cargo_hold.push(CargoItem::Drone(drone));
+        }
+        Ok(())
+    }
+
+    // move a power-up from the cargo hold to the fitting
+    pub fn equip(&mut self, cargo_index: usize) -> Result<()> {
+        match self.cargo_hold.get(cargo_index) {
+            Some(CargoItem::Module(module)) => require!(
+                self.can_fit_module(module),
+                HologramError::NoRoomToFitModule
+            ),
+            Some(CargoItem::Drone(drone)) => {
+                require!(self.can_load_drone(drone), HologramError::DroneBayFull)
+            }
+            Some(CargoItem::Mutation(_)) => {}
+            None => return err!(HologramError::InvalidPowerUpIndex),
+        }
+        match self.cargo_hold.remove(cargo_index) {
+            CargoItem::Module(module) => self.modules.push(module),
+            CargoItem::Drone(drone) => self.drones.push(drone),
+            CargoItem::Mutation(mutation) => self.mutations.push(mutation),
+        }
+        Ok(())
+    }
+
+    // move a fitted power-up to the cargo hold
+    pub fn unequip(&mut self, powerup_type: PowerUpType, index: usize) -> Result<()> {
+        let item = self.remove_fitted(powerup_type, index)?;
+        self.cargo_hold.push(item);
+        Ok(())
+    }
+
+    // take a fitted power-up off the spaceship, along with its gambits
+    pub fn remove_fitted(&mut self, powerup_type: PowerUpType, index: usize) -> Result<CargoItem> {
+        let fitted_count = match powerup_type {
+            PowerUpType::Module => self.modules.len(),
+            PowerUpType::Drone => self.drones.len(),
+            PowerUpType::Mutation => self.mutations.len(),
+        };
+        require!(index < fitted_count, HologramError::InvalidPowerUpIndex);
+        let item = match powerup_type {
+            PowerUpType::Module => CargoItem::Module(self.modules.remove(index)),
+            PowerUpType::Drone => CargoItem::Drone(self.drones.remove(index)),
+            PowerUpType::Mutation => CargoItem::Mutation(self.mutations.remove(index)),
+        };
+        // the power-ups after the removed one moved down by one
+        self.gambits
+            .retain(|g| !(g.powerup_type == powerup_type && g.index as usize == index));
+        for gambit in self.gambits.iter_mut() {
+            if gambit.powerup_type == powerup_type && gambit.index as usize > index {
+                gambit.index -= 1;
+            }
+        }
+        Ok(item)
+    }
+
+    // take a power-up off the spaceship for good, wherever it sits
+    pub fn remove_powerup(&mut self, location: PowerUpLocation) -> Result<CargoItem> {
+        match location {
+            PowerUpLocation::Fitted {
+                powerup_type,
+                index,
+            } => self.remove_fitted(powerup_type, index as usize),
+            PowerUpLocation::CargoHold { cargo_index } => {
+                require!(
+                    (cargo_index as usize) < self.cargo_hold.len(),
+                    HologramError::InvalidPowerUpIndex
+                );
+                Ok(self.cargo_hold.remove(cargo_index as usize))
+            }
+        }
+    }
+
+    // replace the gambits, one at most per active power-up
+    pub fn set_gambits(&mut self, gambits: Vec<Gambit>) -> Result<()> {
+        require!(gambits.len() <= MAX_GAMBITS, HologramError::InvalidGambits);
+        for (i, gambit) in gambits.iter().enumerate() {
+            let is_active = match gambit.powerup_type {
+                PowerUpType::Module => self
+                    .modules
+                    .get(gambit.index as usize)
+                    .map(|m| m.is_active()),
+                PowerUpType::Drone => self
+                    .drones
+                    .get(gambit.index as usize)
+                    .map(|d| d.is_active()),
+                PowerUpType::Mutation => None,
+            };
+            require!(is_active == Some(true), HologramError::InvalidPowerUpIndex);
+            require!(
+                !gambits[..i]
+                    .iter()
+                    .any(|g| g.powerup_type == gambit.powerup_type && g.index == gambit.index),
+                HologramError::InvalidGambits
+            );
+            // keep conditions small, negating once is enough
+            if let Condition::Not(condition) = &gambit.condition {
+                require!(
+                    !matches!(**condition, Condition::Not(_)),
+                    HologramError::InvalidGambits
+                );
+            }
+        }
+        self.gambits = gambits;
         Ok(())
     }
 
     pub fn apply_mutation(&mut self, mutation: Mutation) -> Result<()> {
         require!(
-            self.ordnance() < MAX_ORDNANCE,
+            self.owned_powerups() < MAX_ORDNANCE,
             HologramError::MaxOrdnanceReached
         );
         msg!("Mutation applied: {:?}", mutation);
         self.mutations.push(mutation);
         Ok(())
     }
+
+    // raise the tier of a fitted module or drone, mutations have none
+    pub fn upgrade(&mut self, powerup_type: PowerUpType, index: usize) -> Result<(Rarity, Tier)> {
+        let (rarity, tier) = match powerup_type {
+            PowerUpType::Module => {
+                let module = self
+                    .modules
+                    .get_mut(index)
+                    .ok_or(HologramError::InvalidPowerUpIndex)?;
+                (module.rarity, &mut module.tier)
+            }
+            PowerUpType::Drone => {
+                let drone = self
+                    .drones
+                    .get_mut(index)
+                    .ok_or(HologramError::InvalidPowerUpIndex)?;
+                (drone.rarity, &mut drone.tier)
+            }
+            PowerUpType::Mutation => return err!(HologramError::PowerUpNotUpgradable),
+        };
+        *tier = tier.next().ok_or(HologramError::MaxTierReached)?;
+        Ok((rarity, *tier))
+    }
+
+    // remove a copy of the fitted power-up from the cargo hold, it must be of the same tier
+    // Note: to be called before upgrade, while the fitted power-up still has its current tier
+    pub fn consume_duplicate(
+        &mut self,
+        powerup_type: PowerUpType,
+        index: usize,
+        cargo_index: usize,
+    ) -> Result<()> {
+        let is_duplicate = match (powerup_type, self.cargo_hold.get(cargo_index)) {
+            (PowerUpType::Module, Some(CargoItem::Module(copy))) => {
+                matches!(self.modules.get(index), Some(module) if module.name == copy.name && module.tier == copy.tier)
+            }
+            (PowerUpType::Drone, Some(CargoItem::Drone(copy))) => {
+                matches!(self.drones.get(index), Some(drone) if drone.name == copy.name && drone.tier == copy.tier)
+            }
+            _ => false,
+        };
+        require!(is_duplicate, HologramError::InvalidUpgradeDuplicate);
+        self.cargo_hold.remove(cargo_index);
+        Ok(())
+    }
+
+    // fitted and in the cargo hold
+    pub fn owned_mutations(&self) -> Vec<&Mutation> {
+        self.mutations
+            .iter()
+            .chain(self.cargo_hold.iter().filter_map(|item| match item {
+                CargoItem::Mutation(mutation) => Some(mutation),
+                _ => None,
+            }))
+            .collect()
+    }
+
+    // replace the owned mutation of the same family, where it is (the ordnance doesn't change)
+    pub fn evolve_mutation(&mut self, mutation: Mutation) -> Result<()> {
+        let owned = self
+            .mutations
+            .iter_mut()
+            .chain(self.cargo_hold.iter_mut().filter_map(|item| match item {
+                CargoItem::Mutation(mutation) => Some(mutation),
+                _ => None,
+            }))
+            .find(|m| m.family.is_some() && m.family == mutation.family)
+            .ok_or(error!(HologramError::MutationFamilyNotOwned))?;
+        msg!("Mutation evolved: {} -> {}", owned.name, mutation.name);
+        *owned = mutation;
+        Ok(())
+    }
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+// Modules and drones drop as Tier I, see upgrade_powerup
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Tier {
+    I,
+    II,
+    III,
+}
+
+impl Tier {
+    // added to the damage, repair amount or charge burn of the power-up effect
+    pub fn potency_bonus(&self) -> u8 {
+        match self {
+            Tier::I => 0,
+            Tier::II => 1,
+            Tier::III => 2,
+        }
+    }
+
+    pub fn next(&self) -> Option<Tier> {
+        match self {
+            Tier::I => Some(Tier::II),
+            Tier::II => Some(Tier::III),
+            Tier::III => None,
+        }
+    }
+}
+
+// Note: ordered from the most to the least common
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub enum Rarity {
     Common,
     Uncommon,
@@ -205,7 +738,7 @@ pub enum HitpointLayer {
     Shield,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Default)]
 pub struct HitPoints {
     pub current: u8,
     pub max: u8,
@@ -224,6 +757,12 @@ impl HitPoints {
         self.current = self.current.saturating_add(amount);
     }
 
+    // Note: never goes below 1, a spaceship can't start a fight defeated
+    pub fn decrease_max(&mut self, amount: u8) {
+        self.max = std::cmp::max(self.max.saturating_sub(amount), 1);
+        self.current = std::cmp::min(self.current, self.max);
+    }
+
     pub fn depleted(&self) -> bool {
         self.current == 0
     }
@@ -235,6 +774,11 @@ impl HitPoints {
     pub fn resplenish(&mut self, amount: u8) {
         self.current = self.current.saturating_add(amount);
     }
+
+    // Note: unlike resplenish, never goes above max (armor repairs, capacitor recharge)
+    pub fn resplenish_up_to_max(&mut self, amount: u8) {
+        self.current = std::cmp::min(self.current.saturating_add(amount), self.max);
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -243,6 +787,18 @@ pub enum ModuleClass {
     Repairer(Bonuses, RepairModuleStats),
     Capacitative(Bonuses, Passive),
     Jammer(Bonuses, JammerModuleStats),
+    Disruptor(StatusModuleStats),
+    Reactive(Bonuses, ReactiveModuleStats),
+    // weapon shooting the opponent drones down first
+    AntiDrone(WeaponModuleStats),
+    // passive defense against incoming missiles
+    PointDefense(Bonuses, PointDefenseStats),
+    // grows the capacitor or speeds up its recharge
+    Capacitor(Bonuses, CapacitorModuleStats),
+    // only provides bonuses (targeting computers, armor plates...)
+    Upgrade(Bonuses),
+    // trades hull hitpoints for initiative (and dodge chance) through the bonuses
+    Afterburner(Bonuses, AfterburnerStats),
 }
 
 impl PartialEq for ModuleClass {
@@ -257,32 +813,119 @@ pub enum DroneClass {
     Weapon(WeaponModuleStats),
     // Electronic warfare drones
     ECM(JammerModuleStats),
+    // Hunts the opponent drones first
+    Interceptor(WeaponModuleStats),
+    // Shoots incoming missiles down
+    PointDefense(PointDefenseStats),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub enum MutationClass {
+    // permanent stats bonuses
+    Growth(Bonuses),
+    // passive effect released each time the mutation has cooled down
+    Metabolic(Bonuses, MetabolicStats),
+    // alters the way all the spaceship powerups behave
+    Adaptive(Bonuses, ShipModifier),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum MutationFamily {
+    Nanite,
+    Regeneration,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct MetabolicStats {
+    pub effect: MetabolicEffect,
+    pub heat: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub enum MetabolicEffect {
+    Repair {
+        target: RepairTarget,
+        amount: u8,
+    },
+    Status {
+        status: StatusKind,
+        target: StatusTarget,
+        duration: u8,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub enum ShipModifier {
+    // shortens the charge time of all active powerups (never below 1)
+    ChargeTimeReduction(u8),
+    // extra damage per shot for all weapons of the given type
+    WeaponDamage(WeaponType, u8),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
 pub enum DroneSize {
     Light,
     Medium,
     Heavy,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+impl DroneSize {
+    // structure points of the drone once launched
+    pub fn hitpoints(&self) -> u8 {
+        match self {
+            DroneSize::Light => 2,
+            DroneSize::Medium => 4,
+            DroneSize::Heavy => 6,
+        }
+    }
+
+    // room taken in the drone bay, see SlotLayout
+    pub fn bay_usage(&self) -> u8 {
+        match self {
+            DroneSize::Light => 1,
+            DroneSize::Medium => 2,
+            DroneSize::Heavy => 3,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
 pub enum Shots {
     Single,
     Salvo(u8),
 }
 
+impl Shots {
+    pub fn count(&self) -> u8 {
+        match self {
+            Shots::Single => 1,
+            Shots::Salvo(shots) => *shots,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct WeaponModuleStats {
     pub weapon_type: WeaponType,
     pub damage: u8,
+    // when set, each shot rolls its damage between damage and max_damage
+    pub max_damage: Option<u8>,
     pub shots: Shots,
     pub charge_time: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+// What a weapon shoots at
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Targeting {
+    Ship,
+    // the opponent drones still flying, then the ship once they are all down
+    DronesFirst,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
 pub enum RepairTarget {
     Hull,
+    Armor,
     Shield,
 }
 
@@ -296,9 +939,14 @@ pub struct RepairModuleStats {
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Default)]
 pub struct Bonuses {
     pub hull_hitpoints: u8,
+    pub armor_hitpoints: u8,
     pub shield_layers: u8,
     pub dodge_chance: u8,
     pub jamming_nullifying_chance: u8,
+    pub critical_hit_chance: u8,
+    // added to the critical hit damage multiplier, in percent
+    pub critical_hit_damage: u8,
+    pub initiative: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -316,6 +964,92 @@ pub enum Passive {
 pub struct JammerModuleStats {
     pub charge_burn: u8,
     pub charge_time: u8,
+    // energy removed from the target capacitor
+    pub capacitor_drain: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub struct CapacitorModuleStats {
+    pub capacity: u8,
+    pub recharge_per_turn: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub struct AfterburnerStats {
+    // removed from the hull max hitpoints
+    pub hull_penalty: u8,
+}
+
+// Attempts to shoot down each incoming missile shot before it lands
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub struct PointDefenseStats {
+    pub intercept_chance: u8,
+    // each attempt heats the powerup, it can't intercept again until cooled down
+    pub heat: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
+pub struct StatusModuleStats {
+    pub status: StatusKind,
+    pub target: StatusTarget,
+    // how many turns the status lasts
+    pub duration: u8,
+    pub charge_time: u8,
+}
+
+// Timed buffs and debuffs applied to a spaceship during a fight
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum StatusKind {
+    // drones stop charging
+    DronesDisabled,
+    // hull damage at the end of each turn
+    Burn { damage: u8 },
+    // lowers the chance to avoid attacks
+    DodgePenalty { amount: u8 },
+    // added to the charge gained each turn by active powerups (slow when negative)
+    ChargeRate { modifier: i8 },
+}
+
+impl StatusKind {
+    // stackable statuses add up their intensity when applied again, the others only refresh their duration
+    pub fn is_stackable(&self) -> bool {
+        match self {
+            StatusKind::DronesDisabled => false,
+            StatusKind::Burn { .. }
+            | StatusKind::DodgePenalty { .. }
+            | StatusKind::ChargeRate { .. } => true,
+        }
+    }
+}
+
+// Passive effect released in reaction to what happens to the spaceship during a fight
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct ReactiveModuleStats {
+    pub trigger: Trigger,
+    pub effect: Effect,
+    // can't react again until cooled down (at least 1 turn)
+    pub heat: u8,
+}
+
+// What a reactive powerup is subscribed to, from the point of view of its owner
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    // took a shot (after the dodge roll)
+    OnHit,
+    OnDodge,
+    OnShieldLayerDown,
+    // one of the active powerups lost charge to a jam
+    OnJammed,
+    // took hull damage and the hull is below the given percentage of its max HP
+    OnHullBelowPercent(u8),
+    OnOpponentRepair,
+}
+
+// Who the status is applied to, relative to the powerup owner
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
+pub enum StatusTarget {
+    Origin,
+    Target,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq)]
@@ -326,55 +1060,369 @@ pub enum WeaponType {
     Plasma,
 }
 
-#[cfg(test)]
+impl WeaponType {
+    // capacitor energy drained when a weapon of this type fires
+    pub fn energy_cost(&self) -> u8 {
+        match self {
+            WeaponType::Projectile => 1,
+            WeaponType::Missile => 2,
+            WeaponType::Laser => 3,
+            WeaponType::Plasma => 3,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
 pub fn mock_spaceship(
     modules: Vec<Module>,
     drones: Vec<Drone>,
     mutations: Vec<Mutation>,
 ) -> SpaceShip {
     SpaceShip {
-        bump: 0,
-        owner: Pubkey::default(),
-        id: 0,
-        name: LimitedString::new("Mock Spaceship"),
-        analytics: SpaceShipAnalytics {
-            total_arena_matches: 0,
-            total_arena_victories: 0,
-        },
-        randomness: Randomness {
-            switchboard_request_info: SwitchboardRequestInfo {
-                account: Pubkey::default(),
-                status: SwitchboardFunctionRequestStatus::Settled { slot: 0 },
+        modules,
+        drones,
+        mutations,
+        ..SpaceShip::new_offchain(LimitedString::new("Mock Spaceship"), Hull::CommonOne)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            engine::{
+                SpaceShipBattleCard, LT_DRONE_COMMON, LT_DRONE_OFFENSIVE_RARE, LT_MODULES_COMMON,
+                LT_MODULES_RARE, LT_MUTATIONS_RARE, LT_MUTATIONS_UNCOMMON,
             },
-            original_seed: 0,
-            current_seed: 0,
-            iteration: 0,
+            DODGE_CHANCE_CAP,
         },
-        arena_matchmaking: ArenaMatchmaking {
-            switchboard_request_info: SwitchboardRequestInfo {
-                account: Pubkey::default(),
-                status: SwitchboardFunctionRequestStatus::Settled { slot: 0 },
+    };
+
+    #[test]
+    fn test_record_drop() {
+        let mut crate_picking = mock_spaceship(vec![], vec![], vec![]).crate_picking;
+
+        // the counter is tracked per crate type and resets on a Rare or better
+        for _ in 0..3 {
+            crate_picking.record_drop(CrateType::NavyIssue, Rarity::Common);
+        }
+        crate_picking.record_drop(CrateType::PirateContraband, Rarity::Uncommon);
+        assert_eq!(crate_picking.pity_counter(CrateType::NavyIssue), 3);
+        assert_eq!(crate_picking.pity_counter(CrateType::PirateContraband), 1);
+        assert_eq!(crate_picking.pity_counter(CrateType::BiomechanicalCache), 0);
+        crate_picking.record_drop(CrateType::NavyIssue, Rarity::Rare);
+        crate_picking.record_drop(CrateType::PirateContraband, Rarity::Faction);
+        assert_eq!(crate_picking.pity_counter(CrateType::NavyIssue), 0);
+        assert_eq!(crate_picking.pity_counter(CrateType::PirateContraband), 0);
+
+        // saturates
+        crate_picking.pity_counters[CrateType::BiomechanicalCache as usize] = u8::MAX;
+        crate_picking.record_drop(CrateType::BiomechanicalCache, Rarity::Common);
+        assert_eq!(
+            crate_picking.pity_counter(CrateType::BiomechanicalCache),
+            u8::MAX
+        );
+    }
+
+    #[test]
+    fn test_evolve_mutation() {
+        let uncommon = LT_MUTATIONS_UNCOMMON
+            .iter()
+            .find(|m| m.family == Some(MutationFamily::Nanite))
+            .unwrap()
+            .clone();
+        let rare = LT_MUTATIONS_RARE
+            .iter()
+            .find(|m| m.family == Some(MutationFamily::Nanite))
+            .unwrap()
+            .clone();
+        let mut spaceship = mock_spaceship(vec![], vec![], vec![]);
+        spaceship.cargo_hold.push(CargoItem::Mutation(uncommon));
+
+        // replaced in place, wherever it sits
+        spaceship.evolve_mutation(rare.clone()).unwrap();
+        assert_eq!(spaceship.owned_powerups(), 1);
+        assert!(matches!(&spaceship.cargo_hold[0], CargoItem::Mutation(m) if m.name == rare.name));
+
+        // a mutation without an owned family never evolves anything
+        let regeneration = LT_MUTATIONS_RARE
+            .iter()
+            .find(|m| m.family == Some(MutationFamily::Regeneration))
+            .unwrap()
+            .clone();
+        assert!(spaceship.evolve_mutation(regeneration).is_err());
+        let familyless = LT_MUTATIONS_RARE
+            .iter()
+            .find(|m| m.family.is_none())
+            .unwrap()
+            .clone();
+        assert!(spaceship.evolve_mutation(familyless).is_err());
+    }
+
+    #[test]
+    fn test_upgrade_with_duplicate() {
+        let pulse_laser = LT_MODULES_COMMON[0].clone();
+        let hornet = LT_DRONE_COMMON[0].clone();
+        let mut spaceship = mock_spaceship(vec![pulse_laser.clone()], vec![hornet.clone()], vec![]);
+        spaceship.cargo_hold = vec![
+            CargoItem::Module(LT_MODULES_COMMON[1].clone()),
+            CargoItem::Drone(hornet.clone()),
+            CargoItem::Module(pulse_laser.clone()),
+        ];
+
+        // only a copy of the fitted power-up can pay for its upgrade
+        assert!(spaceship
+            .consume_duplicate(PowerUpType::Module, 0, 0)
+            .is_err());
+        assert!(spaceship
+            .consume_duplicate(PowerUpType::Module, 0, 1)
+            .is_err());
+        assert!(spaceship
+            .consume_duplicate(PowerUpType::Module, 0, 3)
+            .is_err());
+        spaceship
+            .consume_duplicate(PowerUpType::Module, 0, 2)
+            .unwrap();
+        assert_eq!(
+            spaceship.upgrade(PowerUpType::Module, 0).unwrap(),
+            (Rarity::Common, Tier::II)
+        );
+        assert_eq!(spaceship.modules[0].tier, Tier::II);
+        assert_eq!(spaceship.cargo_hold.len(), 2);
+
+        // the copy must be of the same tier
+        spaceship.upgrade(PowerUpType::Drone, 0).unwrap();
+        assert!(spaceship
+            .consume_duplicate(PowerUpType::Drone, 0, 1)
+            .is_err());
+        spaceship.cargo_hold[1] = CargoItem::Drone(Drone {
+            tier: Tier::II,
+            ..hornet
+        });
+        spaceship
+            .consume_duplicate(PowerUpType::Drone, 0, 1)
+            .unwrap();
+        assert_eq!(
+            spaceship.upgrade(PowerUpType::Drone, 0).unwrap(),
+            (Rarity::Common, Tier::III)
+        );
+        assert_eq!(spaceship.cargo_hold.len(), 1);
+    }
+
+    #[test]
+    fn test_module_fitting() {
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let mut spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let slot_layout = spaceship.hull.slot_layout();
+
+        // weapon slots fill up, then the overflow goes to the cargo hold
+        for _ in 0..slot_layout.weapon_slots + 1 {
+            spaceship.mount_module(pulse_laser_module.clone()).unwrap();
+        }
+        assert_eq!(spaceship.slot_usage(Slot::Weapon), slot_layout.weapon_slots);
+        assert_eq!(spaceship.cargo_hold.len(), 1);
+        assert_eq!(spaceship.ordnance(), slot_layout.weapon_slots);
+
+        // utility slots are free, but the power grid can run out first
+        let mut power_hungry_module = LT_MODULES_RARE
+            .into_iter()
+            .find(|m| m.slot() == Slot::Utility)
+            .unwrap();
+        power_hungry_module.rarity = Rarity::Faction;
+        while spaceship.can_fit_module(&power_hungry_module) {
+            spaceship.mount_module(power_hungry_module.clone()).unwrap();
+        }
+        assert!(spaceship.slot_usage(Slot::Utility) < slot_layout.utility_slots);
+        assert!(spaceship.power_usage() <= slot_layout.power_grid);
+        spaceship.mount_module(power_hungry_module).unwrap();
+        assert_eq!(spaceship.cargo_hold.len(), 2);
+
+        // owned power-ups, fitted or not, are capped
+        while spaceship.owned_powerups() < MAX_ORDNANCE {
+            spaceship.mount_module(pulse_laser_module.clone()).unwrap();
+        }
+        assert!(spaceship.mount_module(pulse_laser_module).is_err());
+    }
+
+    #[test]
+    fn test_drone_bay_capacity() {
+        let hornet_drone = LT_DRONE_COMMON[0].clone();
+        let vespa_drone = LT_DRONE_OFFENSIVE_RARE[0].clone();
+        let mut spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let drone_bay = spaceship.hull.slot_layout().drone_bay;
+
+        for _ in 0..(drone_bay - 1) {
+            spaceship.load_drone(hornet_drone.clone()).unwrap();
+        }
+        // a medium drone doesn't fit in the last slot and goes to the cargo hold, a light one does fit
+        spaceship.load_drone(vespa_drone).unwrap();
+        assert_eq!(spaceship.cargo_hold.len(), 1);
+        spaceship.load_drone(hornet_drone.clone()).unwrap();
+        assert_eq!(spaceship.drone_bay_usage(), drone_bay);
+        spaceship.load_drone(hornet_drone).unwrap();
+        assert_eq!(spaceship.drones.len() as u8, drone_bay);
+        assert_eq!(spaceship.cargo_hold.len(), 2);
+    }
+
+    #[test]
+    fn test_equip_and_unequip_powerups() {
+        let pulse_laser_module = LT_MODULES_COMMON
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Pulse Laser"))
+            .unwrap();
+        let hornet_drone = LT_DRONE_COMMON[0].clone();
+        let mut spaceship = mock_spaceship(vec![pulse_laser_module], vec![hornet_drone], vec![]);
+
+        spaceship.unequip(PowerUpType::Drone, 0).unwrap();
+        spaceship.unequip(PowerUpType::Module, 0).unwrap();
+        assert!(spaceship.unequip(PowerUpType::Module, 0).is_err());
+        assert_eq!(spaceship.ordnance(), 0);
+        assert_eq!(spaceship.owned_powerups(), 2);
+
+        // the hold keeps the order power-ups were stowed in
+        spaceship.equip(1).unwrap();
+        assert_eq!(spaceship.modules.len(), 1);
+        assert!(spaceship.equip(1).is_err());
+        spaceship.equip(0).unwrap();
+        assert_eq!(spaceship.drones.len(), 1);
+        assert!(spaceship.cargo_hold.is_empty());
+
+        // a module can't be equipped without room to fit it
+        let weapon_slots = spaceship.hull.slot_layout().weapon_slots;
+        for _ in 0..weapon_slots {
+            spaceship
+                .mount_module(spaceship.modules[0].clone())
+                .unwrap();
+        }
+        assert_eq!(spaceship.cargo_hold.len(), 1);
+        assert!(spaceship.equip(0).is_err());
+        spaceship.unequip(PowerUpType::Module, 0).unwrap();
+        spaceship.equip(0).unwrap();
+        assert_eq!(spaceship.slot_usage(Slot::Weapon), weapon_slots);
+    }
+
+    #[test]
+    fn test_hull_profiles() {
+        let mut spaceship = mock_spaceship(vec![], vec![], vec![]);
+        let s = SpaceShipBattleCard::new(&spaceship);
+        assert_eq!(s.hull_hitpoints.max, BASE_HULL_HITPOINTS);
+        assert_eq!(s.dodge_chance, BASE_DODGE_CHANCE);
+
+        spaceship.hull = Hull::RareTwo;
+        let s = SpaceShipBattleCard::new(&spaceship);
+        let profile = Hull::RareTwo.profile();
+        assert_eq!(s.hull_hitpoints.max, profile.hull_hitpoints);
+        assert_eq!(s.shield_layers.current, profile.shield_layers);
+        assert_eq!(s.dodge_chance, profile.dodge_chance);
+        assert_eq!(
+            s.jamming_nullifying_chance,
+            profile.jamming_nullifying_chance
+        );
+
+        // bonuses stack on top of the hull, up to the caps
+        let kinetic_tendons_mutation = LT_MUTATIONS_RARE
+            .into_iter()
+            .find(|m| m.name == LimitedString::new("Kinetic Tendons"))
+            .unwrap();
+        spaceship.mutations = vec![kinetic_tendons_mutation; 6];
+        let s = SpaceShipBattleCard::new(&spaceship);
+        assert_eq!(s.dodge_chance, DODGE_CHANCE_CAP);
+    }
+
+    #[test]
+    fn test_powerup_tiers() {
+        let pulse_laser = LT_MODULES_COMMON[0].clone();
+        let hornet = LT_DRONE_COMMON[0].clone();
+        let mut spaceship = mock_spaceship(
+            vec![pulse_laser.clone()],
+            vec![hornet.clone()],
+            vec![LT_MUTATIONS_UNCOMMON[0].clone()],
+        );
+
+        // each tier adds to the effect potency
+        assert_eq!(
+            spaceship.upgrade(PowerUpType::Module, 0).unwrap(),
+            (Rarity::Common, Tier::II)
+        );
+        assert_eq!(
+            spaceship.upgrade(PowerUpType::Module, 0).unwrap(),
+            (Rarity::Common, Tier::III)
+        );
+        assert!(spaceship.upgrade(PowerUpType::Module, 0).is_err());
+        assert!(spaceship.upgrade(PowerUpType::Mutation, 0).is_err());
+        assert!(spaceship.upgrade(PowerUpType::Drone, 1).is_err());
+        assert_eq!(
+            spaceship.modules[0]
+                .get_effect()
+                .unwrap()
+                .potential_damage(),
+            pulse_laser.get_effect().unwrap().potential_damage() + Tier::III.potency_bonus()
+        );
+        spaceship.upgrade(PowerUpType::Drone, 0).unwrap();
+        assert_eq!(
+            spaceship.drones[0].get_effect().unwrap().potential_damage(),
+            hornet.get_effect().unwrap().potential_damage() + Tier::II.potency_bonus()
+        );
+    }
+
+    #[test]
+    fn test_remove_powerup() {
+        let mut spaceship = mock_spaceship(
+            vec![LT_MODULES_COMMON[0].clone(), LT_MODULES_COMMON[1].clone()],
+            vec![],
+            vec![LT_MUTATIONS_UNCOMMON[0].clone()],
+        );
+        spaceship.gambits = vec![
+            Gambit {
+                powerup_type: PowerUpType::Module,
+                index: 0,
+                condition: Condition::TurnAtLeast(1),
             },
-            matchmaking_status: MatchMakingStatus::None,
-        },
-        crate_picking: CratePicking {
-            switchboard_request_info: SwitchboardRequestInfo {
-                account: Pubkey::default(),
-                status: SwitchboardFunctionRequestStatus::Settled { slot: 0 },
+            Gambit {
+                powerup_type: PowerUpType::Module,
+                index: 1,
+                condition: Condition::TurnAtLeast(1),
             },
-        },
-        hull: Hull::CommonOne,
-        fuel: Fuel {
-            max: 0,
-            current: 0,
-            daily_allowance_last_collection: 0,
-        },
-        wallet: Wallet {
-            imperial_credits: 0,
-            activate_nanite_paste: 0,
-        },
-        modules,
-        drones,
-        mutations,
+        ];
+
+        // the salvaged power-up leaves the spaceship for good, its gambits with it
+        let salvaged = spaceship.remove_fitted(PowerUpType::Module, 0).unwrap();
+        assert!(matches!(salvaged, CargoItem::Module(m) if m.name == LT_MODULES_COMMON[0].name));
+        assert_eq!(spaceship.owned_powerups(), 2);
+        assert!(spaceship.cargo_hold.is_empty());
+        assert_eq!(spaceship.gambits.len(), 1);
+        assert_eq!(spaceship.gambits[0].index, 0);
+        assert!(spaceship.remove_fitted(PowerUpType::Drone, 0).is_err());
+
+        // the cargo hold can be salvaged too
+        spaceship.unequip(PowerUpType::Mutation, 0).unwrap();
+        let salvaged = spaceship
+            .remove_powerup(PowerUpLocation::CargoHold { cargo_index: 0 })
+            .unwrap();
+        assert!(
+            matches!(salvaged, CargoItem::Mutation(m) if m.name == LT_MUTATIONS_UNCOMMON[0].name)
+        );
+        assert_eq!(spaceship.owned_powerups(), 1);
+        assert!(spaceship
+            .remove_powerup(PowerUpLocation::CargoHold { cargo_index: 0 })
+            .is_err());
+    }
+
+    #[test]
+    fn test_resplenish_up_to_max() {
+        let mut hitpoints = HitPoints::init(10);
+        hitpoints.deplete(4);
+        hitpoints.resplenish_up_to_max(3);
+        assert_eq!(hitpoints.current, 9);
+        hitpoints.resplenish_up_to_max(3);
+        assert_eq!(hitpoints.current, hitpoints.max);
+
+        // resplenish isn't bound by max
+        hitpoints.resplenish(3);
+        assert_eq!(hitpoints.current, 13);
     }
 }
@@ -1,6 +1,6 @@
 use {crate::error::HologramError, anchor_lang::prelude::*};
 
-#[derive(Clone, Copy)]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy)]
 pub enum Currency {
     ImperialCredit,
     ActivateNanitePaste,
@@ -0,0 +1,51 @@
+pub use crate::utils;
+use {
+    crate::utils::pda,
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    hologram::state::LootTable,
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+    tokio::sync::RwLock,
+};
+
+pub async fn initialize_loot_table(
+    program_test_ctx: &RwLock<ProgramTestContext>,
+    admin: &Keypair,
+    realm_pda: &Pubkey,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let (loot_table_pda, loot_table_bump) = pda::get_loot_table_pda(realm_pda);
+
+    let accounts_meta = {
+        let accounts = hologram::accounts::InitializeLootTable {
+            admin: admin.pubkey(),
+            realm: *realm_pda,
+            loot_table: loot_table_pda,
+            system_program: anchor_lang::system_program::ID,
+        };
+
+        let accounts_meta = accounts.to_account_metas(None);
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_hologram_ix(
+        program_test_ctx,
+        accounts_meta,
+        hologram::instruction::InitializeLootTable {},
+        Some(&admin.pubkey()),
+        &[admin],
+        None,
+        None,
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let loot_table = utils::get_account::<LootTable>(program_test_ctx, &loot_table_pda).await;
+
+    assert_eq!(loot_table.bump, loot_table_bump);
+    assert_eq!(loot_table.realm, *realm_pda);
+    assert_eq!(loot_table.entries.len(), LootTable::DEFAULT_ENTRIES);
+    assert_eq!(loot_table.crate_odds.len(), LootTable::DEFAULT_CRATE_ODDS);
+    Ok(())
+}
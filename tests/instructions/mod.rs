@@ -2,10 +2,18 @@ pub mod arena_matchmaking;
 pub mod claim_fuel_allowance;
 pub mod create_spaceship;
 pub mod create_user_account;
+pub mod equip_powerup;
+pub mod initialize_loot_table;
 pub mod initialize_realm;
 pub mod pick_crate;
+pub mod salvage_powerup;
+pub mod set_crate_odds;
+pub mod set_gambits;
+pub mod unequip_powerup;
+pub mod upgrade_powerup;
 
 pub use {
     arena_matchmaking::*, claim_fuel_allowance::*, create_spaceship::*, create_user_account::*,
-    initialize_realm::*, pick_crate::*,
+    equip_powerup::*, initialize_loot_table::*, initialize_realm::*, pick_crate::*,
+    salvage_powerup::*, set_crate_odds::*, set_gambits::*, unequip_powerup::*, upgrade_powerup::*,
 };
@@ -3,8 +3,9 @@ use {
     crate::{utils::pda, IMPERIUM_CPF, SWITCHBOARD_ATTESTATION_QUEUE},
     anchor_lang::ToAccountMetas,
     hologram::{
+        engine::{LootEngine, MutationDrop},
         instructions::CrateType,
-        state::{SpaceShip, SwitchboardFunctionRequestStatus},
+        state::{LootTable, SpaceShip, SwitchboardFunctionRequestStatus},
         utils::RandomNumberGenerator,
     },
     solana_program::pubkey::Pubkey,
@@ -83,12 +84,14 @@ pub async fn pick_crate(
 
     // ==== WHEN ==============================================================
     let enclave_signer = Keypair::new();
+    let (loot_table_pda, _) = pda::get_loot_table_pda(realm_pda);
     let accounts_meta = {
         let accounts = hologram::accounts::PickCrateSettle {
             enclave_signer: enclave_signer.pubkey(), // In the real world this is not called by anyone else than the docker container
             user: user.pubkey(),
             realm: *realm_pda,
             user_account: user_account_pda,
+            loot_table: loot_table_pda,
             spaceship: spaceship_pda,
             crate_picking_function: Pubkey::from_str(IMPERIUM_CPF).unwrap(),
             switchboard_request: switchboard_cpf_request,
@@ -125,18 +128,15 @@ pub async fn pick_crate(
         SwitchboardFunctionRequestStatus::Settled { slot: _ }
     ));
 
-    // verify that the crate cost was debited
-    let price = crate_type.crate_price();
-    let currency = crate_type.payment_currency();
-    assert_eq!(
-        spaceship_before.wallet.get_balance(currency) - price as u16,
-        spaceship.wallet.get_balance(currency) as u16
-    );
-
     // verify presence of drop (the RNG is deterministic)
     let mut rng = RandomNumberGenerator::new(generated_seed as u64);
     let crate_outcome_roll = rng.roll_dice(100) as u8;
-    let crate_outcome = crate_type.determine_outcome(crate_outcome_roll);
+    let loot_table = utils::get_account::<LootTable>(program_test_ctx, &loot_table_pda).await;
+    let crate_odds = loot_table
+        .get_crate_odds(crate_type)
+        .expect("every crate type has odds");
+    let crate_outcome = crate_odds.determine_outcome(crate_outcome_roll);
+    let mut refunded = false;
     match crate_outcome {
         hologram::instructions::CrateOutcome::Module { .. } => {
             assert_eq!(spaceship.modules.len(), spaceship_before.modules.len() + 1);
@@ -147,11 +147,37 @@ pub async fn pick_crate(
             assert_eq!(spaceship.ordnance(), spaceship_before.ordnance() + 1);
         }
         hologram::instructions::CrateOutcome::Mutation => {
-            assert_eq!(
-                spaceship.mutations.len(),
-                spaceship_before.mutations.len() + 1
-            );
-            assert_eq!(spaceship.ordnance(), spaceship_before.ordnance() + 1);
+            let pity_bonus =
+                crate_odds.pity_bonus(spaceship_before.crate_picking.pity_counter(crate_type));
+            let mutation_drop = LootEngine::drop_mutation(
+                &mut rng,
+                &loot_table,
+                &spaceship_before.owned_mutations(),
+                pity_bonus,
+            )
+            .expect("the loot table is valid");
+            match mutation_drop {
+                MutationDrop::New(mutation) => {
+                    assert_eq!(
+                        spaceship.mutations.len(),
+                        spaceship_before.mutations.len() + 1
+                    );
+                    assert_eq!(spaceship.mutations.last().unwrap().name, mutation.name);
+                    assert_eq!(spaceship.ordnance(), spaceship_before.ordnance() + 1);
+                }
+                // the owned mutation of the family is replaced
+                MutationDrop::Evolution(mutation) => {
+                    assert_eq!(spaceship.owned_powerups(), spaceship_before.owned_powerups());
+                    assert!(spaceship
+                        .owned_mutations()
+                        .iter()
+                        .any(|m| m.name == mutation.name));
+                }
+                MutationDrop::Exhausted => {
+                    assert_eq!(spaceship.owned_powerups(), spaceship_before.owned_powerups());
+                    refunded = true;
+                }
+            }
         }
         hologram::instructions::CrateOutcome::Scam => {
             assert_eq!(spaceship.modules.len(), spaceship_before.modules.len());
@@ -161,5 +187,24 @@ pub async fn pick_crate(
         }
     }
 
+    // verify that the crate cost was debited (unless refunded)
+    let price = if refunded { 0 } else { crate_type.crate_price() };
+    let currency = crate_type.payment_currency();
+    assert_eq!(
+        spaceship_before.wallet.get_balance(currency) - price as u16,
+        spaceship.wallet.get_balance(currency) as u16
+    );
+
+    // verify that the pity counter either reset (Rare or better) or went up, a scam or refund leaves it untouched
+    let pity_counter_before = spaceship_before.crate_picking.pity_counter(crate_type);
+    let pity_counter = spaceship.crate_picking.pity_counter(crate_type);
+    match crate_outcome {
+        hologram::instructions::CrateOutcome::Scam => {
+            assert_eq!(pity_counter, pity_counter_before)
+        }
+        _ if refunded => assert_eq!(pity_counter, pity_counter_before),
+        _ => assert!(pity_counter == 0 || pity_counter == pity_counter_before + 1),
+    }
+
     Ok(())
 }
@@ -0,0 +1,106 @@
+pub use crate::utils;
+use {
+    crate::utils::pda,
+    anchor_lang::ToAccountMetas,
+    hologram::{
+        instructions::salvage_refund,
+        state::{CargoItem, Currency, PowerUpLocation, PowerUpType, SpaceShip, Tier},
+    },
+    solana_program::pubkey::Pubkey,
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+    tokio::sync::RwLock,
+};
+
+pub async fn salvage_powerup(
+    program_test_ctx: &RwLock<ProgramTestContext>,
+    user: &Keypair,
+    realm_pda: &Pubkey,
+    spaceship_index: u8,
+    location: PowerUpLocation,
+) -> std::result::Result<(), BanksClientError> {
+    let (spaceship_pda, _) = utils::get_spaceship_pda(realm_pda, &user.pubkey(), spaceship_index);
+    let spaceship_before = utils::get_account::<SpaceShip>(program_test_ctx, &spaceship_pda).await;
+    let spaceship_lamports_before = program_test_ctx
+        .write()
+        .await
+        .banks_client
+        .get_balance(spaceship_pda)
+        .await
+        .unwrap();
+
+    // ==== WHEN ==============================================================
+    let (user_account_pda, _) = pda::get_user_account_pda(&realm_pda, &user.pubkey());
+
+    let accounts_meta = {
+        let accounts = hologram::accounts::SalvagePowerup {
+            user: user.pubkey(),
+            realm: *realm_pda,
+            user_account: user_account_pda,
+            spaceship: spaceship_pda,
+            system_program: solana_program::system_program::id(),
+        };
+
+        let accounts_meta = accounts.to_account_metas(None);
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_hologram_ix(
+        program_test_ctx,
+        accounts_meta,
+        hologram::instruction::SalvagePowerup {
+            spaceship_index,
+            location,
+        },
+        Some(&user.pubkey()),
+        &[user],
+        None,
+        None,
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let spaceship = utils::get_account::<SpaceShip>(program_test_ctx, &spaceship_pda).await;
+    let spaceship_lamports = program_test_ctx
+        .write()
+        .await
+        .banks_client
+        .get_balance(spaceship_pda)
+        .await
+        .unwrap();
+
+    // verify that the power-up is gone and refunded
+    assert!(spaceship.owned_powerups() == spaceship_before.owned_powerups() - 1);
+    let powerup = match location {
+        PowerUpLocation::Fitted {
+            powerup_type,
+            index,
+        } => match powerup_type {
+            PowerUpType::Module => {
+                CargoItem::Module(spaceship_before.modules[index as usize].clone())
+            }
+            PowerUpType::Drone => CargoItem::Drone(spaceship_before.drones[index as usize].clone()),
+            PowerUpType::Mutation => {
+                CargoItem::Mutation(spaceship_before.mutations[index as usize].clone())
+            }
+        },
+        PowerUpLocation::CargoHold { cargo_index } => {
+            spaceship_before.cargo_hold[cargo_index as usize].clone()
+        }
+    };
+    let (rarity, tier, currency) = match powerup {
+        CargoItem::Module(module) => (module.rarity, module.tier, Currency::ImperialCredit),
+        CargoItem::Drone(drone) => (drone.rarity, drone.tier, Currency::ImperialCredit),
+        CargoItem::Mutation(mutation) => (mutation.rarity, Tier::I, Currency::ActivateNanitePaste),
+    };
+    assert_eq!(
+        spaceship.wallet.get_balance(currency),
+        spaceship_before.wallet.get_balance(currency) + salvage_refund(rarity, tier) as u16
+    );
+
+    // verify that the account shrank, its rent going back to the user
+    assert!(spaceship_lamports < spaceship_lamports_before);
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+pub use crate::utils;
+use {
+    crate::utils::pda,
+    anchor_lang::{prelude::Pubkey, ToAccountMetas},
+    hologram::state::{CrateOdds, LootTable},
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+    tokio::sync::RwLock,
+};
+
+pub async fn set_crate_odds(
+    program_test_ctx: &RwLock<ProgramTestContext>,
+    admin: &Keypair,
+    realm_pda: &Pubkey,
+    crate_odds: CrateOdds,
+) -> std::result::Result<(), BanksClientError> {
+    // ==== WHEN ==============================================================
+    let (loot_table_pda, _) = pda::get_loot_table_pda(realm_pda);
+
+    let accounts_meta = {
+        let accounts = hologram::accounts::SetCrateOdds {
+            admin: admin.pubkey(),
+            realm: *realm_pda,
+            loot_table: loot_table_pda,
+        };
+
+        let accounts_meta = accounts.to_account_metas(None);
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_hologram_ix(
+        program_test_ctx,
+        accounts_meta,
+        hologram::instruction::SetCrateOdds { crate_odds },
+        Some(&admin.pubkey()),
+        &[admin],
+        None,
+        None,
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let loot_table = utils::get_account::<LootTable>(program_test_ctx, &loot_table_pda).await;
+
+    let odds = loot_table
+        .get_crate_odds(crate_odds.crate_type)
+        .expect("every crate type has odds");
+    assert_eq!(odds.module_weight, crate_odds.module_weight);
+    assert_eq!(odds.drone_weight, crate_odds.drone_weight);
+    assert_eq!(odds.mutation_weight, crate_odds.mutation_weight);
+    assert_eq!(odds.scam_weight, crate_odds.scam_weight);
+    assert_eq!(odds.pity_threshold, crate_odds.pity_threshold);
+    assert_eq!(odds.pity_rarity_bonus, crate_odds.pity_rarity_bonus);
+    Ok(())
+}
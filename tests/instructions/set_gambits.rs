@@ -0,0 +1,59 @@
+pub use crate::utils;
+use {
+    crate::utils::pda,
+    anchor_lang::ToAccountMetas,
+    hologram::state::{Gambit, SpaceShip},
+    solana_program::pubkey::Pubkey,
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+    tokio::sync::RwLock,
+};
+
+pub async fn set_gambits(
+    program_test_ctx: &RwLock<ProgramTestContext>,
+    user: &Keypair,
+    realm_pda: &Pubkey,
+    spaceship_index: u8,
+    gambits: Vec<Gambit>,
+) -> std::result::Result<(), BanksClientError> {
+    let (spaceship_pda, _) = utils::get_spaceship_pda(realm_pda, &user.pubkey(), spaceship_index);
+
+    // ==== WHEN ==============================================================
+    let (user_account_pda, _) = pda::get_user_account_pda(&realm_pda, &user.pubkey());
+
+    let accounts_meta = {
+        let accounts = hologram::accounts::SetGambits {
+            user: user.pubkey(),
+            realm: *realm_pda,
+            user_account: user_account_pda,
+            spaceship: spaceship_pda,
+            system_program: solana_program::system_program::id(),
+        };
+
+        let accounts_meta = accounts.to_account_metas(None);
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_hologram_ix(
+        program_test_ctx,
+        accounts_meta,
+        hologram::instruction::SetGambits {
+            spaceship_index,
+            gambits: gambits.clone(),
+        },
+        Some(&user.pubkey()),
+        &[user],
+        None,
+        None,
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let spaceship = utils::get_account::<SpaceShip>(program_test_ctx, &spaceship_pda).await;
+
+    // verify that the gambits were stored
+    assert!(spaceship.gambits == gambits);
+
+    Ok(())
+}
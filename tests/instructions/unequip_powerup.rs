@@ -0,0 +1,63 @@
+pub use crate::utils;
+use {
+    crate::utils::pda,
+    anchor_lang::ToAccountMetas,
+    hologram::state::{PowerUpType, SpaceShip},
+    solana_program::pubkey::Pubkey,
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+    tokio::sync::RwLock,
+};
+
+pub async fn unequip_powerup(
+    program_test_ctx: &RwLock<ProgramTestContext>,
+    user: &Keypair,
+    realm_pda: &Pubkey,
+    spaceship_index: u8,
+    powerup_type: PowerUpType,
+    index: u8,
+) -> std::result::Result<(), BanksClientError> {
+    let (spaceship_pda, _) = utils::get_spaceship_pda(realm_pda, &user.pubkey(), spaceship_index);
+    let spaceship_before = utils::get_account::<SpaceShip>(program_test_ctx, &spaceship_pda).await;
+
+    // ==== WHEN ==============================================================
+    let (user_account_pda, _) = pda::get_user_account_pda(&realm_pda, &user.pubkey());
+
+    let accounts_meta = {
+        let accounts = hologram::accounts::UnequipPowerup {
+            user: user.pubkey(),
+            realm: *realm_pda,
+            user_account: user_account_pda,
+            spaceship: spaceship_pda,
+            system_program: solana_program::system_program::id(),
+        };
+
+        let accounts_meta = accounts.to_account_metas(None);
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_hologram_ix(
+        program_test_ctx,
+        accounts_meta,
+        hologram::instruction::UnequipPowerup {
+            spaceship_index,
+            powerup_type,
+            index,
+        },
+        Some(&user.pubkey()),
+        &[user],
+        None,
+        None,
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let spaceship = utils::get_account::<SpaceShip>(program_test_ctx, &spaceship_pda).await;
+
+    // verify that the power-up left the fitting for the cargo hold
+    assert!(spaceship.cargo_hold.len() == spaceship_before.cargo_hold.len() + 1);
+    assert!(spaceship.ordnance() == spaceship_before.ordnance() - 1);
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+pub use crate::utils;
+use {
+    crate::utils::pda,
+    anchor_lang::ToAccountMetas,
+    hologram::{
+        instructions::{upgrade_price, UPGRADE_CURRENCY},
+        state::{PowerUpType, SpaceShip},
+    },
+    solana_program::pubkey::Pubkey,
+    solana_program_test::{BanksClientError, ProgramTestContext},
+    solana_sdk::signer::{keypair::Keypair, Signer},
+    tokio::sync::RwLock,
+};
+
+pub async fn upgrade_powerup(
+    program_test_ctx: &RwLock<ProgramTestContext>,
+    user: &Keypair,
+    realm_pda: &Pubkey,
+    spaceship_index: u8,
+    powerup_type: PowerUpType,
+    index: u8,
+    duplicate_cargo_index: Option<u8>,
+) -> std::result::Result<(), BanksClientError> {
+    let (spaceship_pda, _) = utils::get_spaceship_pda(realm_pda, &user.pubkey(), spaceship_index);
+    let spaceship_before = utils::get_account::<SpaceShip>(program_test_ctx, &spaceship_pda).await;
+
+    // ==== WHEN ==============================================================
+    let (user_account_pda, _) = pda::get_user_account_pda(&realm_pda, &user.pubkey());
+
+    let accounts_meta = {
+        let accounts = hologram::accounts::UpgradePowerup {
+            user: user.pubkey(),
+            realm: *realm_pda,
+            user_account: user_account_pda,
+            spaceship: spaceship_pda,
+        };
+
+        let accounts_meta = accounts.to_account_metas(None);
+
+        accounts_meta
+    };
+
+    utils::create_and_execute_hologram_ix(
+        program_test_ctx,
+        accounts_meta,
+        hologram::instruction::UpgradePowerup {
+            spaceship_index,
+            powerup_type,
+            index,
+            duplicate_cargo_index,
+        },
+        Some(&user.pubkey()),
+        &[user],
+        None,
+        None,
+    )
+    .await?;
+
+    // ==== THEN ==============================================================
+    let spaceship = utils::get_account::<SpaceShip>(program_test_ctx, &spaceship_pda).await;
+
+    // verify that the tier went up and was paid for
+    let (rarity, tier_before, tier) = match powerup_type {
+        PowerUpType::Module => (
+            spaceship.modules[index as usize].rarity,
+            spaceship_before.modules[index as usize].tier,
+            spaceship.modules[index as usize].tier,
+        ),
+        PowerUpType::Drone => (
+            spaceship.drones[index as usize].rarity,
+            spaceship_before.drones[index as usize].tier,
+            spaceship.drones[index as usize].tier,
+        ),
+        PowerUpType::Mutation => unreachable!("mutations can't be upgraded"),
+    };
+    assert_eq!(tier_before.next(), Some(tier));
+    let price = upgrade_price(rarity, tier, duplicate_cargo_index.is_some());
+    if duplicate_cargo_index.is_some() {
+        assert_eq!(
+            spaceship.cargo_hold.len(),
+            spaceship_before.cargo_hold.len() - 1
+        );
+    }
+    assert_eq!(
+        spaceship.wallet.get_balance(UPGRADE_CURRENCY),
+        spaceship_before.wallet.get_balance(UPGRADE_CURRENCY) - price
+    );
+
+    Ok(())
+}
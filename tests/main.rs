@@ -2,6 +2,7 @@ use {
     crate::utils::pda,
     hologram::{
         instructions::{CrateType, Faction},
+        state::{CrateOdds, PowerUpLocation, PowerUpType},
         FUEL_ALLOWANCE_COOLDOWN,
     },
     instructions::utils::warp_forward,
@@ -99,6 +100,44 @@ pub async fn test_integration() {
         .unwrap();
     }
 
+    // [1 bis] ------------------------------ INITIALIZE LOOT TABLE -------------------------------
+    {
+        instructions::initialize_loot_table(&program_test_ctx, &keypairs[ADMIN], &realm_pda)
+            .await
+            .unwrap();
+    }
+
+    // [1 ter] ------------------------------ SET CRATE ODDS --------------------------------------
+    // The odds must total 100
+    // ---------------------------------------------------------------------------------------------
+    {
+        let crate_odds = CrateOdds {
+            crate_type: CrateType::PirateContraband,
+            module_weight: 45,
+            drone_weight: 40,
+            mutation_weight: 7,
+            scam_weight: 8,
+            faction_rarity_enabled: true,
+            pity_threshold: 6,
+            pity_rarity_bonus: 10,
+        };
+        instructions::set_crate_odds(&program_test_ctx, &keypairs[ADMIN], &realm_pda, crate_odds)
+            .await
+            .unwrap();
+
+        assert!(instructions::set_crate_odds(
+            &program_test_ctx,
+            &keypairs[ADMIN],
+            &realm_pda,
+            CrateOdds {
+                scam_weight: 50,
+                ..crate_odds
+            },
+        )
+        .await
+        .is_err());
+    }
+
     // [2] --------------------------------- CREATE USER ACCOUNT ---------------------------------
     {
         let mut create_user_account_tasks = vec![];
@@ -222,6 +261,57 @@ pub async fn test_integration() {
         }
     }
 
+    // [5 bis] ---------------------- UNEQUIP POWERUP (should fail) --------------------------------
+    // The fitting is locked while the crate picking is pending settlement
+    // ---------------------------------------------------------------------------------------------
+    {
+        let user = &keypairs[USER_1];
+        let spaceship_index = 0;
+
+        assert!(instructions::unequip_powerup(
+            &program_test_ctx,
+            &user,
+            &realm_pda,
+            spaceship_index,
+            PowerUpType::Module,
+            0,
+        )
+        .await
+        .is_err());
+    }
+
+    // [5 ter] ---------------------- UPGRADE POWERUP (should fail) --------------------------------
+    // Mutations have no tier, and the crate left too few ImperialCredits to upgrade the starter module
+    // ---------------------------------------------------------------------------------------------
+    {
+        let user = &keypairs[USER_1];
+        let spaceship_index = 0;
+
+        assert!(instructions::upgrade_powerup(
+            &program_test_ctx,
+            &user,
+            &realm_pda,
+            spaceship_index,
+            PowerUpType::Mutation,
+            0,
+            None,
+        )
+        .await
+        .is_err());
+
+        assert!(instructions::upgrade_powerup(
+            &program_test_ctx,
+            &user,
+            &realm_pda,
+            spaceship_index,
+            PowerUpType::Module,
+            0,
+            None,
+        )
+        .await
+        .is_err());
+    }
+
     // [6] -------------------- ARENA MATCHMAKING (queue filling) ----------------------------------
     // Start by placing 5 players in the queue
     // ---------------------------------------------------------------------------------------------
@@ -276,4 +366,25 @@ pub async fn test_integration() {
         .await
         .unwrap();
     }
+
+    // [8] ---------------------------- SALVAGE POWERUP --------------------------------------------
+    // Scrap the starter module of the first player for a few ImperialCredits (its match is settled)
+    // ---------------------------------------------------------------------------------------------
+    {
+        let user = &keypairs[USER_1];
+        let spaceship_index = 0;
+
+        instructions::salvage_powerup(
+            &program_test_ctx,
+            &user,
+            &realm_pda,
+            spaceship_index,
+            PowerUpLocation::Fitted {
+                powerup_type: PowerUpType::Module,
+                index: 0,
+            },
+        )
+        .await
+        .unwrap();
+    }
 }
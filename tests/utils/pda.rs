@@ -36,3 +36,7 @@ pub fn get_switchboard_state_pda() -> (Pubkey, u8) {
         &switchboard_solana::SWITCHBOARD_ATTESTATION_PROGRAM_ID,
     )
 }
+
+pub fn get_loot_table_pda(realm_pda: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"loot_table", realm_pda.as_ref()], &hologram::id())
+}
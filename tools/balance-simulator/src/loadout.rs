@@ -0,0 +1,176 @@
+use hologram::{
+    engine::{LootEngine, MutationDrop},
+    instructions::user_facing::Faction,
+    state::{DroneClass, Hull, LootTable, ModuleClass, SpaceShip},
+    utils::{LimitedString, RandomNumberGenerator},
+    ARENA_MATCHMAKING_ORDNANCE_PER_RANGE, MAX_ORDNANCE,
+};
+
+// Odds of each powerup type when filling a loadout, totalling 100
+pub const MODULE_CHANCE: u64 = 60;
+pub const DRONE_CHANCE: u64 = 30;
+// Drops rolled before giving up on reaching the ordnance, a spaceship can run out of room
+// for modules and drones once it owns every mutation
+pub const MAX_DROP_ATTEMPTS: u32 = 1000;
+
+// A generated spaceship and the tags used to aggregate its results
+pub struct Loadout {
+    pub spaceship: SpaceShip,
+    // the faction the spaceship queued for, decides the currency of its rewards
+    pub faction: Faction,
+    pub tags: LoadoutTags,
+}
+
+#[derive(Default)]
+pub struct LoadoutTags {
+    pub powerups: Vec<String>,
+    pub weapon_types: Vec<String>,
+    pub rarities: Vec<String>,
+    pub ordnance_band: String,
+}
+
+// Roll a spaceship with a random amount of powerups, dropped the same way crates do
+// Note: without the crates pity, the loadouts aren't a history of picks
+pub fn generate_loadout(
+    rng: &mut RandomNumberGenerator,
+    loot_table: &LootTable,
+    faction_rarity_enabled: bool,
+) -> Loadout {
+    let hull = Hull::roll(rng);
+    let faction = roll_faction(rng);
+    let ordnance = rng.roll_dice(MAX_ORDNANCE as usize) as u8;
+    let mut spaceship = SpaceShip::new_offchain(LimitedString::new("Simulated Spaceship"), hull);
+    let mut tags = LoadoutTags::default();
+
+    let mut attempts = 0;
+    while spaceship.ordnance() < ordnance && attempts < MAX_DROP_ATTEMPTS {
+        attempts += 1;
+        let roll = rng.roll_dice(100);
+        if roll <= MODULE_CHANCE {
+            let module = LootEngine::drop_module(rng, loot_table, faction_rarity_enabled, 0)
+                .expect("module loot tables aren't empty");
+            if !spaceship.can_fit_module(&module) {
+                continue;
+            }
+            if let ModuleClass::Weapon(wms) | ModuleClass::AntiDrone(wms) = &module.class {
+                tags.weapon_types.push(format!("{:?}", wms.weapon_type));
+            }
+            tags.powerups.push(module.name.to_string());
+            tags.rarities.push(format!("{:?}", module.rarity));
+            spaceship.modules.push(module);
+        } else if roll <= MODULE_CHANCE + DRONE_CHANCE {
+            let drone = LootEngine::drop_drone(rng, loot_table, faction_rarity_enabled, 0)
+                .expect("drone loot tables aren't empty");
+            if !spaceship.can_load_drone(&drone) {
+                continue;
+            }
+            if let DroneClass::Weapon(wms) | DroneClass::Interceptor(wms) = &drone.class {
+                tags.weapon_types.push(format!("{:?}", wms.weapon_type));
+            }
+            tags.powerups.push(drone.name.to_string());
+            tags.rarities.push(format!("{:?}", drone.rarity));
+            spaceship.drones.push(drone);
+        } else {
+            let drop = LootEngine::drop_mutation(rng, loot_table, &spaceship.owned_mutations(), 0)
+                .expect("mutation loot tables aren't empty");
+            match drop {
+                MutationDrop::New(mutation) => {
+                    tags.powerups.push(mutation.name.to_string());
+                    tags.rarities.push(format!("{:?}", mutation.rarity));
+                    spaceship.mutations.push(mutation);
+                }
+                MutationDrop::Evolution(mutation) => {
+                    // the evolved mutation isn't part of the loadout anymore
+                    let evolved = spaceship
+                        .mutations
+                        .iter_mut()
+                        .find(|m| m.family == mutation.family)
+                        .expect("evolutions come from an owned family");
+                    let evolved_name = evolved.name.to_string();
+                    tags.powerups.retain(|name| *name != evolved_name);
+                    tags.powerups.push(mutation.name.to_string());
+                    tags.rarities.push(format!("{:?}", mutation.rarity));
+                    *evolved = mutation;
+                }
+                MutationDrop::Exhausted => continue,
+            }
+        }
+    }
+
+    tags.ordnance_band = ordnance_band(spaceship.ordnance());
+
+    // a loadout carrying twice the same thing only counts once per tag
+    for tag_list in [
+        &mut tags.powerups,
+        &mut tags.weapon_types,
+        &mut tags.rarities,
+    ] {
+        tag_list.sort();
+        tag_list.dedup();
+    }
+
+    Loadout {
+        spaceship,
+        faction,
+        tags,
+    }
+}
+
+// every faction has the same odds
+pub fn roll_faction(rng: &mut RandomNumberGenerator) -> Faction {
+    match rng.roll_dice(3) {
+        1 => Faction::Imperium,
+        2 => Faction::Pirate,
+        3 => Faction::RogueDrone,
+        _ => panic!("Invalid dice roll"),
+    }
+}
+
+// Same ranges as the realm matchmaking queues
+pub fn ordnance_band(ordnance: u8) -> String {
+    let from =
+        ordnance / ARENA_MATCHMAKING_ORDNANCE_PER_RANGE * ARENA_MATCHMAKING_ORDNANCE_PER_RANGE;
+    format!(
+        "{:02}-{:02}",
+        from,
+        from + ARENA_MATCHMAKING_ORDNANCE_PER_RANGE - 1
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::collections::HashSet};
+
+    #[test]
+    fn test_generate_loadout_is_deterministic() {
+        let loot_table = LootTable::default();
+        let generate = |seed| {
+            let mut rng = RandomNumberGenerator::new(seed);
+            (0..20)
+                .map(|_| generate_loadout(&mut rng, &loot_table, true))
+                .collect::<Vec<_>>()
+        };
+        let describe = |loadouts: &[Loadout]| {
+            loadouts
+                .iter()
+                .map(|l| format!("{:?} {:?} {:?}", l.spaceship, l.faction, l.tags.powerups))
+                .collect::<Vec<_>>()
+        };
+
+        let loadouts = generate(1);
+        assert_eq!(describe(&loadouts), describe(&generate(1)));
+        assert_ne!(describe(&loadouts), describe(&generate(2)));
+
+        // hulls and factions are rolled too
+        let hulls = loadouts
+            .iter()
+            .map(|l| format!("{:?}", l.spaceship.hull))
+            .collect::<HashSet<_>>();
+        let factions = loadouts
+            .iter()
+            .map(|l| format!("{:?}", l.faction))
+            .collect::<HashSet<_>>();
+        assert!(hulls.len() > 1);
+        assert!(factions.len() > 1);
+    }
+}
@@ -0,0 +1,212 @@
+// Monte Carlo balance simulator
+//
+// Runs seeded fights between loadouts rolled from the loot tables and reports win rates per powerup,
+// weapon type, rarity and ordnance band. Same seed, same report.
+//
+// cargo run --release -p balance-simulator -- --fights 10000 --seed 1 --format csv --out balance_report
+
+pub mod loadout;
+pub mod stats;
+
+use {
+    hologram::{
+        engine::{Environment, FightEngine, FightOutcome, SpaceShipBattleCard},
+        state::LootTable,
+        utils::RandomNumberGenerator,
+        MATCH_MAX_TURN,
+    },
+    loadout::generate_loadout,
+    stats::Report,
+    std::{fs, path::PathBuf},
+};
+
+pub enum OutputFormat {
+    Csv,
+    Json,
+}
+
+pub struct Config {
+    pub fights: u32,
+    pub seed: u64,
+    pub faction_rarity_enabled: bool,
+    pub format: OutputFormat,
+    pub out: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fights: 10_000,
+            seed: 1,
+            faction_rarity_enabled: false,
+            format: OutputFormat::Json,
+            out: PathBuf::from("balance_report"),
+        }
+    }
+}
+
+const USAGE: &str =
+    "usage: balance-simulator [--fights N] [--seed N] [--faction] [--format csv|json] [--out DIR]";
+
+fn parse_args() -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {}", arg));
+        match arg.as_str() {
+            "--fights" => {
+                config.fights = value()?.parse().map_err(|e| format!("--fights: {}", e))?
+            }
+            "--seed" => config.seed = value()?.parse().map_err(|e| format!("--seed: {}", e))?,
+            "--faction" => config.faction_rarity_enabled = true,
+            "--format" => {
+                config.format = match value()?.as_str() {
+                    "csv" => OutputFormat::Csv,
+                    "json" => OutputFormat::Json,
+                    other => return Err(format!("unknown format {}", other)),
+                }
+            }
+            "--out" => config.out = PathBuf::from(value()?),
+            "--help" | "-h" => return Err(USAGE.to_string()),
+            other => return Err(format!("unknown argument {}\n{}", other, USAGE)),
+        }
+    }
+    if config.seed == 0 {
+        // xorshift would be stuck on 0
+        return Err("--seed must not be 0".to_string());
+    }
+    Ok(config)
+}
+
+pub fn simulate(config: &Config) -> Report {
+    let mut rng = RandomNumberGenerator::new(config.seed);
+    let mut report = Report::default();
+    let mut total_turns: u64 = 0;
+    let mut total_currency: u64 = 0;
+    // the loot table a freshly initialized realm starts with
+    let loot_table = LootTable::default();
+
+    for _ in 0..config.fights {
+        let mut user = generate_loadout(&mut rng, &loot_table, config.faction_rarity_enabled);
+        let mut opponent = generate_loadout(&mut rng, &loot_table, config.faction_rarity_enabled);
+        let fight_seed = rng.next() as u32;
+        // same odds as arena_matchmaking_settle
+        let environment = Environment::roll(&mut rng);
+
+        let mut fight_engine = FightEngine::new(Box::new(|_| {}));
+        let outcome = fight_engine.fight(
+            &mut SpaceShipBattleCard::new(&user.spaceship),
+            &mut SpaceShipBattleCard::new(&opponent.spaceship),
+            environment,
+            fight_seed,
+            MATCH_MAX_TURN,
+        );
+        total_turns += fight_engine.turns_played() as u64;
+
+        FightEngine::distribute_arena_currency(
+            &mut user.spaceship,
+            &mut opponent.spaceship,
+            user.faction,
+            outcome,
+        )
+        .expect("fresh wallets can't overflow");
+        // rewards are paid in the legal tender of the user faction
+        let currency = user.faction.legal_tender();
+        total_currency += (user.spaceship.wallet.get_balance(currency)
+            + opponent.spaceship.wallet.get_balance(currency)) as u64;
+
+        let summary = &mut report.summary;
+        summary.fights += 1;
+        match outcome {
+            FightOutcome::UserWon => summary.user_wins += 1,
+            FightOutcome::OpponentWon => summary.opponent_wins += 1,
+            FightOutcome::Draw => summary.draws += 1,
+        }
+
+        let (u, o) = (&user.tags, &opponent.tags);
+        report.powerups.record(&u.powerups, &o.powerups, outcome);
+        report
+            .weapon_types
+            .record(&u.weapon_types, &o.weapon_types, outcome);
+        report.rarities.record(&u.rarities, &o.rarities, outcome);
+        report.ordnance_bands.record(
+            std::slice::from_ref(&u.ordnance_band),
+            std::slice::from_ref(&o.ordnance_band),
+            outcome,
+        );
+    }
+
+    if report.summary.fights > 0 {
+        report.summary.average_turns = total_turns as f64 / report.summary.fights as f64;
+        report.summary.average_currency_reward =
+            total_currency as f64 / report.summary.fights as f64;
+    }
+    report
+}
+
+fn write_report(config: &Config, report: &Report) -> std::io::Result<()> {
+    fs::create_dir_all(&config.out)?;
+    match config.format {
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(report).expect("report is serializable");
+            fs::write(config.out.join("report.json"), json)?;
+        }
+        OutputFormat::Csv => {
+            let summary = serde_json::to_value(&report.summary).expect("summary is serializable");
+            let mut csv = String::from("metric,value\n");
+            for (metric, value) in summary.as_object().expect("summary is a struct") {
+                csv.push_str(&format!("{},{}\n", metric, value));
+            }
+            fs::write(config.out.join("summary.csv"), csv)?;
+            for (name, matrix) in report.matrices() {
+                fs::write(config.out.join(format!("{}.csv", name)), matrix.to_csv())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let config = match parse_args() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    let report = simulate(&config);
+    if let Err(error) = write_report(&config, &report) {
+        eprintln!("failed to write the report: {}", error);
+        std::process::exit(1);
+    }
+
+    let summary = &report.summary;
+    println!(
+        "{} fights: {} user wins, {} opponent wins, {} draws, {:.1} turns on average. Report written to {}",
+        summary.fights,
+        summary.user_wins,
+        summary.opponent_wins,
+        summary.draws,
+        summary.average_turns,
+        config.out.display()
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulate_is_deterministic() {
+        let config = Config {
+            fights: 200,
+            ..Config::default()
+        };
+        let report = serde_json::to_value(simulate(&config)).unwrap();
+        assert_eq!(report, serde_json::to_value(simulate(&config)).unwrap());
+
+        let other_seed = Config { seed: 2, ..config };
+        assert_ne!(report, serde_json::to_value(simulate(&other_seed)).unwrap());
+    }
+}
@@ -0,0 +1,118 @@
+use {
+    hologram::engine::FightOutcome,
+    serde::Serialize,
+    std::{collections::BTreeMap, fmt::Write},
+};
+
+#[derive(Serialize, Default, Clone, Copy)]
+pub struct Cell {
+    pub fights: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub win_rate: f64,
+}
+
+// Results of the row tag against the column tag, from the row point of view
+#[derive(Serialize, Default)]
+pub struct WinRateMatrix(pub BTreeMap<String, BTreeMap<String, Cell>>);
+
+impl WinRateMatrix {
+    // record the outcome of a fight for every pair of tags (both ways)
+    pub fn record(
+        &mut self,
+        user_tags: &[String],
+        opponent_tags: &[String],
+        outcome: FightOutcome,
+    ) {
+        for user_tag in user_tags {
+            for opponent_tag in opponent_tags {
+                self.record_one(
+                    user_tag,
+                    opponent_tag,
+                    outcome == FightOutcome::UserWon,
+                    outcome == FightOutcome::Draw,
+                );
+                self.record_one(
+                    opponent_tag,
+                    user_tag,
+                    outcome == FightOutcome::OpponentWon,
+                    outcome == FightOutcome::Draw,
+                );
+            }
+        }
+    }
+
+    fn record_one(&mut self, row: &str, column: &str, won: bool, draw: bool) {
+        let cell = self
+            .0
+            .entry(row.to_string())
+            .or_default()
+            .entry(column.to_string())
+            .or_default();
+        cell.fights += 1;
+        cell.wins += won as u32;
+        cell.draws += draw as u32;
+        cell.win_rate = cell.wins as f64 / cell.fights as f64;
+    }
+
+    // one line per (row, column) pair
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("row,column,fights,wins,draws,win_rate\n");
+        for (row, columns) in self.0.iter() {
+            for (column, cell) in columns.iter() {
+                writeln!(
+                    csv,
+                    "{},{},{},{},{},{:.4}",
+                    escape_csv(row),
+                    escape_csv(column),
+                    cell.fights,
+                    cell.wins,
+                    cell.draws,
+                    cell.win_rate
+                )
+                .expect("writing to a String");
+            }
+        }
+        csv
+    }
+}
+
+#[derive(Serialize, Default)]
+pub struct Summary {
+    pub fights: u32,
+    pub user_wins: u32,
+    pub opponent_wins: u32,
+    pub draws: u32,
+    pub average_turns: f64,
+    // currency distributed per fight, with the current arena rewards
+    pub average_currency_reward: f64,
+}
+
+#[derive(Serialize, Default)]
+pub struct Report {
+    pub summary: Summary,
+    pub powerups: WinRateMatrix,
+    pub weapon_types: WinRateMatrix,
+    pub rarities: WinRateMatrix,
+    pub ordnance_bands: WinRateMatrix,
+}
+
+impl Report {
+    pub fn matrices(&self) -> [(&str, &WinRateMatrix); 4] {
+        [
+            ("powerups", &self.powerups),
+            ("weapon_types", &self.weapon_types),
+            ("rarities", &self.rarities),
+            ("ordnance_bands", &self.ordnance_bands),
+        ]
+    }
+}
+
+// powerup names can contain commas and quotes ('Halberd' Slicer)
+fn escape_csv(value: &str) -> String {
+    if value.contains(',') || value.contains('"') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}